@@ -1,28 +1,82 @@
 pub mod debugger;
+pub mod disassembler;
 pub mod helpers;
+pub mod network;
+mod bytes;
+mod error;
 mod instruction;
 mod input;
+mod loader;
 mod output;
+mod snapshot;
 mod value;
 
-pub use self::instruction::IntcodeInstruction;
-pub use self::input::{IntcodeInput, IntcodeConsoleInput, IntcodePresetInput, IntcodeBlockingInput};
-pub use self::output::{IntcodeOutput, IntcodeConsoleOutput, IntcodeHistoryOutput};
+use anyhow::{bail, Result};
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+pub use self::error::IntcodeError;
+pub use self::instruction::{IntcodeInstruction, IntcodeAdvance, IntcodeCategory};
+pub use self::input::{IntcodeInput, IntcodeQueuedInput, IntcodeInputPolicy, IntcodeConsoleInput, IntcodePresetInput, IntcodeBlockingInput, IntcodeQueueInput};
+pub use self::output::{IntcodeOutput, IntcodeConsoleOutput, IntcodeHistoryOutput, IntcodeCallbackOutput, IntcodeSinkOutput};
 pub use self::value::IntcodeValue;
 
-#[derive(Debug, PartialEq)]
+// Writes at or past this many cells fail instead of growing memory
+pub const MAX_MEMORY: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum IntcodeState {
     Initialized,
     Running,
     Suspended,
     Halted,
+    Failed(IntcodeError),
+    Breakpoint(usize),
+}
+
+// What stopped step_until_event, with Running only reported by single steps
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntcodeEvent {
+    Running,
+    AwaitingInput,
+    Produced(i64),
+    Halted,
+    Failed(IntcodeError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntcodeWriteEvent {
+    pub pc: usize,
+    pub address: usize,
+    pub old_value: i64,
+    pub new_value: i64,
+}
+
+// Writes seen since the watch was enabled, and (pc, address) for each read of anything else
+#[derive(Clone)]
+struct ReadWatch {
+    written: HashSet<usize>,
+    uninitialized: Vec<(usize, usize)>,
 }
 
+#[derive(Clone)]
 pub struct IntcodeMachine<I, O> {
     state: IntcodeState,
     instruction_pointer: usize,
-    relative_base: usize,
+    relative_base: i64,
+    program: Vec<i64>,
     memory: Vec<i64>,
+    produced_outputs: usize,
+    input_log: Option<Vec<i64>>,
+    max_buffered_outputs: Option<usize>,
+    input_policy: IntcodeInputPolicy,
+    ascii_answer: Option<i64>,
+    verbose: bool,
+    profile: Option<HashMap<&'static str, u64>>,
+    write_trace: Option<Vec<IntcodeWriteEvent>>,
+    read_watch: Option<ReadWatch>,
+    breakpoints: BTreeSet<usize>,
     input_handler: I,
     output_handler: O,
 }
@@ -32,37 +86,361 @@ where I: IntcodeInput,
       O: IntcodeOutput,
 {
     pub fn new(machine_code: &[i64], input_handler: I, output_handler: O) -> Self {
-        let mut memory = vec![0; 1024];
-        for i in 0..machine_code.len() {
-            memory[i] = machine_code[i];
-        }
-        
+        let mut memory = machine_code.to_vec();
+        memory.resize(machine_code.len().max(1024), 0);
+
         Self {
             state: IntcodeState::Initialized,
             instruction_pointer: 0,
             relative_base: 0,
+            program: machine_code.to_vec(),
             memory,
+            produced_outputs: 0,
+            input_log: None,
+            max_buffered_outputs: None,
+            input_policy: IntcodeInputPolicy::Block,
+            ascii_answer: None,
+            verbose: false,
+            profile: None,
+            write_trace: None,
+            read_watch: None,
+            breakpoints: BTreeSet::new(),
             input_handler,
             output_handler,
         }
     }
 
+    pub fn with_output_limit(mut self, max_buffered_outputs: Option<usize>) -> Self {
+        self.max_buffered_outputs = max_buffered_outputs;
+        self
+    }
+
+    // Everything else about the machine is kept, including anything already buffered
+    // by the old handler being dropped with it
+    pub fn with_output_handler<P: IntcodeOutput>(self, output_handler: P) -> IntcodeMachine<I, P> {
+        IntcodeMachine {
+            state: self.state,
+            instruction_pointer: self.instruction_pointer,
+            relative_base: self.relative_base,
+            program: self.program,
+            memory: self.memory,
+            produced_outputs: self.produced_outputs,
+            input_log: self.input_log,
+            max_buffered_outputs: self.max_buffered_outputs,
+            input_policy: self.input_policy,
+            ascii_answer: self.ascii_answer,
+            verbose: self.verbose,
+            profile: self.profile,
+            write_trace: self.write_trace,
+            read_watch: self.read_watch,
+            breakpoints: self.breakpoints,
+            input_handler: self.input_handler,
+            output_handler,
+        }
+    }
+
+    pub fn with_output_callback<F: FnMut(i64) + 'static>(self, f: F) -> IntcodeMachine<I, IntcodeCallbackOutput> {
+        let mut machine = self.with_output_handler(IntcodeCallbackOutput::default());
+        machine.set_output_callback(Box::new(f));
+        machine
+    }
+
+    // Machines block by default, apart from new_with_inputs which pauses
+    pub fn with_input_policy(mut self, input_policy: IntcodeInputPolicy) -> Self {
+        self.input_policy = input_policy;
+        self
+    }
+
+    // Running out of input fails the machine instead of suspending it
+    pub fn with_strict_input(self, strict_input: bool) -> Self {
+        match strict_input {
+            true => self.with_input_policy(IntcodeInputPolicy::Error),
+            false => self.with_input_policy(IntcodeInputPolicy::Block),
+        }
+    }
+
+    // Back to the loaded program with nothing queued, keeping settings, breakpoints and
+    // diagnostics. Memory is refilled in place, so repeated runs don't allocate.
+    pub fn reset(&mut self) {
+        self.state = IntcodeState::Initialized;
+        self.instruction_pointer = 0;
+        self.relative_base = 0;
+
+        self.memory.clear();
+        self.memory.extend_from_slice(&self.program);
+        self.memory.resize(self.program.len().max(1024), 0);
+
+        self.produced_outputs = 0;
+        if let Some(log) = self.input_log.as_mut() {
+            log.clear();
+        }
+        self.ascii_answer = None;
+        if let Some(watch) = self.read_watch.as_mut() {
+            watch.written.clear();
+            watch.uninitialized.clear();
+        }
+
+        self.input_handler.clear();
+        let count = self.output_handler.buffered().len();
+        self.output_handler.take(count);
+    }
+
+    pub fn set_verbose(&mut self, on: bool) {
+        self.verbose = on;
+    }
+
+    // Counts every instruction executed from now on, keyed by mnemonic. Enabling
+    // it again starts the counts over.
+    pub fn enable_profiling(&mut self) {
+        self.profile = Some(HashMap::new());
+    }
+
+    pub fn instruction_counts(&self) -> HashMap<&'static str, u64> {
+        self.profile.clone().unwrap_or_default()
+    }
+
+    pub fn total_instructions(&self) -> u64 {
+        self.profile.iter().flat_map(HashMap::values).sum()
+    }
+
     pub fn run(&mut self) {
+        self.try_run().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    // Failures are also kept in state(). Stopping at a breakpoint isn't a failure,
+    // and running again carries on from the instruction it stopped before.
+    pub fn try_run(&mut self) -> Result<(), IntcodeError> {
+        self.run_limited(None).map(|_| ())
+    }
+
+    // Fails the machine once it has executed max_instructions without stopping
+    pub fn run_with_limit(&mut self, max_instructions: u64) -> Result<IntcodeState, IntcodeError> {
+        match self.run_limited(Some(max_instructions))? {
+            IntcodeState::Running => {
+                let error = IntcodeError::ExecutionLimitExceeded(max_instructions);
+                self.state = IntcodeState::Failed(error.clone());
+                Err(error)
+            },
+            state => Ok(state),
+        }
+    }
+
+    // Executes at most count instructions. A machine that hasn't stopped by then is
+    // left Running, and running it again carries on from there.
+    pub fn run_for(&mut self, count: u64) -> Result<IntcodeState, IntcodeError> {
+        self.run_limited(Some(count))
+    }
+
+    fn run_limited(&mut self, max_instructions: Option<u64>) -> Result<IntcodeState, IntcodeError> {
+        if let IntcodeState::Failed(error) = &self.state {
+            return Err(error.clone());
+        }
+
+        let mut executed = 0;
+        let mut resuming = matches!(self.state, IntcodeState::Breakpoint(_));
         self.state = IntcodeState::Running;
         while self.state == IntcodeState::Running {
-            self.run_next_instruction();
+            if !resuming && self.breakpoints.contains(&self.instruction_pointer) {
+                self.state = IntcodeState::Breakpoint(self.instruction_pointer);
+                break;
+            }
+            if max_instructions.is_some_and(|limit| executed >= limit) {
+                break;
+            }
+            resuming = false;
+            executed += 1;
+            self.run_next_instruction()?;
+        }
+
+        match &self.state {
+            IntcodeState::Failed(error) => Err(error.clone()),
+            state => Ok(state.clone()),
+        }
+    }
+
+    // Counts everything produced, whether or not the output handler keeps it
+    pub fn run_counting_outputs(&mut self) -> (usize, IntcodeState) {
+        let start = self.produced_outputs;
+        let _ = self.try_run();
+        (self.produced_outputs - start, self.state.clone())
+    }
+
+    // Stops right after the next output, leaving the machine Running so run() resumes it
+    pub fn run_until_output(&mut self) -> Option<i64> {
+        match self.step_until_event() {
+            IntcodeEvent::Produced(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    // Pauses before an input that isn't available yet, so feeding it with input() resumes
+    pub fn step_until_event(&mut self) -> IntcodeEvent {
+        loop {
+            match self.step() {
+                IntcodeEvent::Running => continue,
+                event => return event,
+            }
+        }
+    }
+
+    // Executes exactly one instruction, or none if it would wait for input
+    pub fn step(&mut self) -> IntcodeEvent {
+        if let IntcodeState::Failed(error) = &self.state {
+            return IntcodeEvent::Failed(error.clone());
+        }
+
+        let instruction = match self.instruction_at(self.instruction_pointer) {
+            Ok(instruction) => instruction,
+            Err(error) => {
+                self.state = IntcodeState::Failed(error.clone());
+                return IntcodeEvent::Failed(error);
+            },
+        };
+
+        let produced = match &instruction {
+            IntcodeInstruction::Input{..} if self.will_block() => {
+                self.state = IntcodeState::Suspended;
+                return IntcodeEvent::AwaitingInput;
+            },
+            IntcodeInstruction::Output{value} => value.evaluate(&self.memory, self.relative_base),
+            _ => None,
+        };
+
+        self.state = IntcodeState::Running;
+        if let Err(error) = self.operate(instruction) {
+            self.state = IntcodeState::Failed(error);
+        }
+
+        match (&self.state, produced) {
+            (IntcodeState::Failed(error), _) => IntcodeEvent::Failed(error.clone()),
+            (IntcodeState::Halted, _) => IntcodeEvent::Halted,
+            (IntcodeState::Suspended, _) => IntcodeEvent::AwaitingInput,
+            (_, Some(value)) => IntcodeEvent::Produced(value),
+            _ => IntcodeEvent::Running,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) {
+        self.breakpoints.remove(&address);
+    }
+
+    pub fn program_counter(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    // Carries on past any outputs until the machine needs input, halts or fails
+    pub fn resume(&mut self) -> IntcodeEvent {
+        loop {
+            match self.step_until_event() {
+                IntcodeEvent::Produced(_) => continue,
+                event => return event,
+            }
+        }
+    }
+
+    // Values outside ASCII are kept as the answer rather than rendered
+    pub fn run_until_prompt(&mut self) -> Result<String, IntcodeError> {
+        let start = self.output_handler.buffered().len();
+        self.try_run()?;
+        Ok(self.ascii_output_since(start))
+    }
+
+    // A machine that hasn't started is first run to its prompt, which is
+    // included in the returned text
+    pub fn ascii_exchange(&mut self, line: &str) -> Result<String> {
+        if !line.is_ascii() {
+            bail!("Input line is not ASCII: {:?}", line);
+        }
+
+        let start = self.output_handler.buffered().len();
+        if self.state == IntcodeState::Initialized {
+            self.try_run()?;
+        }
+        for byte in line.bytes().chain(std::iter::once(b'\n')) {
+            if self.state != IntcodeState::Suspended {
+                bail!("Machine stopped waiting for input in state {:?}", self.state);
+            }
+            self.input(byte as i64);
+            self.try_run()?;
+        }
+
+        Ok(self.ascii_output_since(start))
+    }
+
+    fn ascii_output_since(&mut self, start: usize) -> String {
+        let values = self.output_handler.buffered()[start..].to_vec();
+        self.ascii_text(values)
+    }
+
+    fn ascii_text(&mut self, values: Vec<i64>) -> String {
+        let mut text = String::new();
+
+        for value in values {
+            match value {
+                0..=127 => text.push(value as u8 as char),
+                _ => self.ascii_answer = Some(value),
+            }
         }
+
+        text
+    }
+
+    // Takes all buffered output. Control characters other than newline are escaped,
+    // and anything outside ASCII is kept as the answer rather than rendered.
+    pub fn read_ascii_output_escaped(&mut self) -> String {
+        let count = self.output_handler.buffered().len();
+        let mut text = String::new();
+
+        for value in self.output_handler.take(count) {
+            match value {
+                10 | 32..=126 => text.push(value as u8 as char),
+                0..=127 => text.push_str(&format!("\\x{:02x}", value)),
+                _ => self.ascii_answer = Some(value),
+            }
+        }
+
+        text
+    }
+
+    // Like read_ascii_output_escaped but without escaping
+    pub fn drain_output_ascii(&mut self) -> String {
+        let values = self.drain_output();
+        self.ascii_text(values)
+    }
+
+    pub fn ascii_answer(&self) -> Option<i64> {
+        self.ascii_answer
     }
 
     pub fn debug(&mut self) -> Vec<IntcodeInstruction> {
         let mut instructions = Vec::new();
         self.state = IntcodeState::Running;
         while self.state == IntcodeState::Running {
-            instructions.push(self.debug_next_instruction());
+            instructions.extend(self.debug_next_instruction());
         }
         instructions
     }
 
+    pub fn trace(&mut self) -> Vec<(usize, IntcodeInstruction)> {
+        let mut trace = Vec::new();
+        self.state = IntcodeState::Running;
+        while self.state == IntcodeState::Running {
+            let address = self.instruction_pointer;
+            if let Some(instruction) = self.debug_next_instruction() {
+                trace.push((address, instruction));
+            }
+        }
+        trace
+    }
+
     pub fn teardown(self) -> (IntcodeState, Vec<i64>, I, O) {
         (self.state, self.memory, self.input_handler, self.output_handler)
     }
@@ -71,22 +449,144 @@ where I: IntcodeInput,
         &self.state
     }
 
+    pub fn ensure_halted(&self) -> Result<()> {
+        match &self.state {
+            IntcodeState::Halted => Ok(()),
+            IntcodeState::Failed(error) => bail!("Machine failed instead of halting: {}", error),
+            state => bail!("Machine stopped at {} in state {:?} instead of halting", self.instruction_pointer, state),
+        }
+    }
+
+    pub fn run_and_checksum(&mut self) -> Result<u64> {
+        self.try_run()?;
+        self.ensure_halted()?;
+        Ok(self.memory_fingerprint())
+    }
+
     pub fn memory(&self) -> &[i64] {
         &self.memory
     }
 
+    // FNV-1a over memory with trailing zeros ignored, so padding doesn't change it
+    pub fn memory_fingerprint(&self) -> u64 {
+        let used = self.memory.iter().rposition(|&cell| cell != 0).map_or(0, |last| last + 1);
+
+        self.memory[..used].iter()
+            .flat_map(|cell| cell.to_le_bytes().to_vec())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+    }
+
+    pub fn find_value(&self, value: i64) -> Vec<usize> {
+        self.memory.iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell == value)
+            .map(|(address, _)| address)
+            .collect()
+    }
+
     pub fn read_memory_position(&self, position: usize) -> i64 {
-        self.memory[position]
+        self.memory.get(position).copied().unwrap_or(0)
     }
 
-    pub fn write_memory(&mut self, position: usize, value: i64) {
+    // Grows memory with zeroes to reach positions past the end
+    pub fn write_memory(&mut self, position: usize, value: i64) -> Result<(), IntcodeError> {
+        if position >= MAX_MEMORY {
+            return Err(IntcodeError::InvalidAddress(position as i64, self.instruction_pointer));
+        }
+
+        if position >= self.memory.len() {
+            self.memory.resize(position + 1, 0);
+        }
+
+        if let Some(trace) = self.write_trace.as_mut() {
+            trace.push(IntcodeWriteEvent {
+                pc: self.instruction_pointer,
+                address: position,
+                old_value: self.memory[position],
+                new_value: value,
+            });
+        }
+
+        if let Some(watch) = self.read_watch.as_mut() {
+            watch.written.insert(position);
+        }
+
         self.memory[position] = value;
+        Ok(())
+    }
+
+    // Writes made with write_memory from outside are traced too, at the current pc
+    pub fn enable_write_trace(&mut self) {
+        if self.write_trace.is_none() {
+            self.write_trace = Some(Vec::new());
+        }
+    }
+
+    pub fn write_trace(&self) -> &[IntcodeWriteEvent] {
+        self.write_trace.as_deref().unwrap_or(&[])
+    }
+
+    // Only writes made after this are known about, so enable it before running
+    pub fn enable_uninitialized_read_warnings(&mut self) {
+        if self.read_watch.is_none() {
+            self.read_watch = Some(ReadWatch { written: HashSet::new(), uninitialized: Vec::new() });
+        }
+    }
+
+    pub fn uninitialized_reads(&self) -> &[(usize, usize)] {
+        self.read_watch.as_ref().map(|watch| &watch.uninitialized[..]).unwrap_or(&[])
+    }
+
+    fn check_uninitialized_reads(&mut self, instruction: &IntcodeInstruction) {
+        if let Some(watch) = self.read_watch.as_mut() {
+            for operand in instruction.read_operands() {
+                if let IntcodeValue::Immediate(_) = operand {
+                    continue;
+                }
+
+                // Negative addresses fail the instruction instead
+                if let Some(address) = operand.address(self.relative_base) {
+                    if address >= self.program.len() && !watch.written.contains(&address) {
+                        watch.uninitialized.push((self.instruction_pointer, address));
+                    }
+                }
+            }
+        }
     }
 
+    // A bad input address fails the machine, like it would while running
     pub fn input(&mut self, value: i64) {
-        let position = self.memory[self.instruction_pointer + 1] as usize;
-        self.write_memory(position, value);
+        if let Err(error) = self.try_input(value) {
+            self.state = IntcodeState::Failed(error);
+        }
+    }
+
+    fn try_input(&mut self, value: i64) -> Result<(), IntcodeError> {
+        let position = match self.instruction_at(self.instruction_pointer) {
+            Ok(IntcodeInstruction::Input{position}) => self.address_of(&position)?,
+            // Treat the raw word after the pointer as the address
+            _ => self.address_of(&IntcodeValue::Immediate(self.read_memory_position(self.instruction_pointer + 1)))?,
+        };
+        self.write_memory(position, value)?;
+        self.log_input(value);
         self.instruction_pointer += 2;
+        Ok(())
+    }
+
+    pub fn enable_input_log(&mut self) {
+        if self.input_log.is_none() {
+            self.input_log = Some(Vec::new());
+        }
+    }
+
+    pub fn consumed_inputs(&self) -> &[i64] {
+        self.input_log.as_deref().unwrap_or(&[])
+    }
+
+    fn log_input(&mut self, value: i64) {
+        if let Some(log) = self.input_log.as_mut() {
+            log.push(value);
+        }
     }
 
     pub fn process_input(&mut self) -> Option<i64> {
@@ -94,9 +594,34 @@ where I: IntcodeInput,
     }
 
     pub fn process_output(&mut self, value: i64) {
+        self.produced_outputs += 1;
         self.output_handler.process(value)
     }
 
+    // The buffer belongs to the output handler, so these only see outputs it keeps
+    pub fn drain_output(&mut self) -> Vec<i64> {
+        let count = self.output_handler.buffered().len();
+        self.output_handler.take(count)
+    }
+
+    pub fn last_output(&self) -> Option<i64> {
+        self.output_handler.buffered().last().copied()
+    }
+
+    // A chunk size of 0 takes nothing
+    pub fn take_output_chunks(&mut self, chunk_size: usize) -> Vec<Vec<i64>> {
+        if chunk_size == 0 {
+            return Vec::new();
+        }
+
+        let complete = self.output_handler.buffered().len() / chunk_size * chunk_size;
+        let values = self.output_handler.take(complete);
+
+        values.chunks(chunk_size)
+            .map(|chunk| chunk.to_vec())
+            .collect()
+    }
+
     pub fn input_handler(&self) -> &I {
         &self.input_handler
     }
@@ -105,107 +630,197 @@ where I: IntcodeInput,
         &self.output_handler
     }
 
-    pub fn run_next_instruction(&mut self) {
-        if self.instruction_pointer >= self.memory.len() {
-            panic!("Instruction pointer out of range")
-        } else {
-            let ptr = self.instruction_pointer;
-            let opcode = self.memory[ptr];
-            let instruction = IntcodeInstruction::new(opcode, &self.memory[ptr+1..]);
-            self.operate(instruction);
+    // Decodes from current memory, reading any parameters past the end as 0
+    pub fn instruction_at(&self, address: usize) -> Result<IntcodeInstruction, IntcodeError> {
+        IntcodeInstruction::decode(&self.memory, address)
+    }
+
+    // Like instruction_at, but missing parameters are an error instead of 0
+    pub fn decode_at(&self, address: usize) -> Result<IntcodeInstruction, IntcodeError> {
+        let instruction = self.instruction_at(address)?;
+        if address + instruction.length() > self.memory.len() {
+            return Err(IntcodeError::TruncatedInstruction(address));
+        }
+        Ok(instruction)
+    }
+
+    pub fn current_category(&self) -> Result<IntcodeCategory, IntcodeError> {
+        self.instruction_at(self.instruction_pointer).map(|instruction| instruction.category())
+    }
+
+    pub fn fork_on_inputs(&self, inputs: &[i64]) -> Vec<(i64, IntcodeMachine<I, O>, Option<i64>)>
+    where I: Clone,
+          O: Clone,
+    {
+        inputs.iter()
+            .map(|&input| {
+                let mut machine = self.clone();
+                if !machine.will_block() {
+                    let _ = machine.try_run();
+                }
+
+                let output = if machine.will_block() {
+                    machine.input(input);
+                    machine.run_until_output()
+                } else {
+                    None
+                };
+
+                (input, machine, output)
+            })
+            .collect()
+    }
+
+    pub fn will_block(&self) -> bool {
+        match self.instruction_at(self.instruction_pointer) {
+            Ok(IntcodeInstruction::Input{..}) => self.input_policy.waits() && !self.input_handler.has_input(),
+            _ => false,
+        }
+    }
+
+    // A word that doesn't decode fails the machine rather than panicking
+    pub fn run_next_instruction(&mut self) -> Result<(), IntcodeError> {
+        self.debug_next_instruction();
+
+        match &self.state {
+            IntcodeState::Failed(error) => Err(error.clone()),
+            _ => Ok(()),
         }
     }
 
-    fn debug_next_instruction(&mut self) -> IntcodeInstruction {
-        if self.instruction_pointer >= self.memory.len() {
-            panic!("Instruction pointer out of range")
-        } else {
-            let ptr = self.instruction_pointer;
-            let opcode = self.memory[ptr];
-            let instruction = IntcodeInstruction::new(opcode, &self.memory[ptr+1..]);
-            // dbg!(&self);
-            // dbg!(&instruction);
-            // let input = crate::utils::input::read_input_with_prompt("").unwrap();
-            // if input != "" {
-            //     panic!("Aborting execution");
-            // }
-            self.operate(instruction.clone());
-            
-            instruction
+    fn debug_next_instruction(&mut self) -> Option<IntcodeInstruction> {
+        match self.instruction_at(self.instruction_pointer) {
+            Ok(instruction) => {
+                if let Err(error) = self.operate(instruction.clone()) {
+                    self.state = IntcodeState::Failed(error);
+                }
+                Some(instruction)
+            },
+            Err(error) => {
+                self.state = IntcodeState::Failed(error);
+                None
+            },
         }
     }
 
-    fn operate(&mut self, instruction: IntcodeInstruction) {
+    fn read(&self, value: &IntcodeValue) -> Result<i64, IntcodeError> {
+        value.evaluate(&self.memory, self.relative_base).ok_or_else(|| self.invalid_address(value))
+    }
+
+    fn address_of(&self, value: &IntcodeValue) -> Result<usize, IntcodeError> {
+        value.address(self.relative_base).ok_or_else(|| self.invalid_address(value))
+    }
+
+    fn invalid_address(&self, value: &IntcodeValue) -> IntcodeError {
+        IntcodeError::InvalidAddress(value.target(self.relative_base), self.instruction_pointer)
+    }
+
+    fn operate(&mut self, instruction: IntcodeInstruction) -> Result<(), IntcodeError> {
         use IntcodeInstruction::*;
+
+        let length = instruction.length();
+
+        if self.verbose {
+            eprintln!("{:>5}: {}", self.instruction_pointer, instruction);
+        }
+
+        if let Some(IntcodeValue::Immediate(_)) = instruction.write_target() {
+            return Err(IntcodeError::WriteToImmediate(self.instruction_pointer));
+        }
+
+        if let Some(profile) = self.profile.as_mut() {
+            *profile.entry(instruction.mnemonic()).or_insert(0) += 1;
+        }
+
+        self.check_uninitialized_reads(&instruction);
         
         match instruction {
             Add{x, y, position} => {
-                let x = x.evaluate(&self.memory, self.relative_base);
-                let y = y.evaluate(&self.memory, self.relative_base);
-                self.write_memory(position, x + y);
-                self.instruction_pointer += 4;
+                let x = self.read(&x)?;
+                let y = self.read(&y)?;
+                let position = self.address_of(&position)?;
+                let sum = x.checked_add(y).ok_or(IntcodeError::ArithmeticOverflow(self.instruction_pointer))?;
+                self.write_memory(position, sum)?;
+                self.instruction_pointer += length;
             },
             Multiply{x, y, position} => {
-                let x = x.evaluate(&self.memory, self.relative_base);
-                let y = y.evaluate(&self.memory, self.relative_base);
-                self.write_memory(position, x * y);
-                self.instruction_pointer += 4;
+                let x = self.read(&x)?;
+                let y = self.read(&y)?;
+                let position = self.address_of(&position)?;
+                let product = x.checked_mul(y).ok_or(IntcodeError::ArithmeticOverflow(self.instruction_pointer))?;
+                self.write_memory(position, product)?;
+                self.instruction_pointer += length;
             },
             Input{position} => {
-                match self.process_input() {
+                let position = self.address_of(&position)?;
+                let input = match self.input_policy {
+                    _ if self.input_handler.has_input() => self.process_input(),
+                    IntcodeInputPolicy::Block => self.process_input(),
+                    IntcodeInputPolicy::Pause => None,
+                    IntcodeInputPolicy::Error => return Err(IntcodeError::InputExhausted(self.instruction_pointer)),
+                    IntcodeInputPolicy::Default(value) => Some(value),
+                };
+
+                match input {
                     Some(input) => { 
-                        self.write_memory(position, input);
-                        self.instruction_pointer += 2;
+                        self.write_memory(position, input)?;
+                        self.log_input(input);
+                        self.instruction_pointer += length;
                     },
                     None => self.state = IntcodeState::Suspended,
                 }
             },
             Output{value} => {
-                self.process_output(value.evaluate(&self.memory, self.relative_base));
-                self.instruction_pointer += 2;
-            },
-            JumpIfTrue{test_position, jump_position} => {
-                let test_value = test_position.evaluate(&self.memory, self.relative_base);
-                if test_value > 0 {
-                    self.instruction_pointer = jump_position.evaluate(&self.memory, self.relative_base) as usize;
-                } else {
-                    self.instruction_pointer += 3;
+                if let Some(limit) = self.max_buffered_outputs {
+                    if self.output_handler.buffered().len() >= limit {
+                        return Err(IntcodeError::OutputLimitExceeded(limit));
+                    }
                 }
+                let value = self.read(&value)?;
+                self.process_output(value);
+                self.instruction_pointer += length;
             },
-            JumpIfFalse{test_position, jump_position} => {
-                let test_value = test_position.evaluate(&self.memory, self.relative_base);
-                if test_value == 0 {
-                    self.instruction_pointer = jump_position.evaluate(&self.memory, self.relative_base) as usize;
-                } else {
-                    self.instruction_pointer += 3;
+            JumpIfTrue{..} | JumpIfFalse{..} => {
+                match instruction.advance(&self.memory, self.relative_base) {
+                    Ok(IntcodeAdvance::Step(step)) => self.instruction_pointer += step,
+                    Ok(IntcodeAdvance::Jump(target)) if target >= 0 && (target as usize) < self.memory.len() => {
+                        self.instruction_pointer = target as usize
+                    },
+                    Ok(IntcodeAdvance::Jump(_)) => return Err(IntcodeError::PcOutOfBounds(self.instruction_pointer)),
+                    Err(operand) => return Err(self.invalid_address(operand)),
                 }
             },
             IsLessThan{x, y, position} => {
-                let x = x.evaluate(&self.memory, self.relative_base);
-                let y = y.evaluate(&self.memory, self.relative_base);
+                let x = self.read(&x)?;
+                let y = self.read(&y)?;
+                let position = self.address_of(&position)?;
                 if x < y {
-                    self.write_memory(position, 1);
+                    self.write_memory(position, 1)?;
                 } else {
-                    self.write_memory(position, 0);
+                    self.write_memory(position, 0)?;
                 }
-                self.instruction_pointer += 4;
+                self.instruction_pointer += length;
             },
             IsEquals{x, y, position} => {
-                let x = x.evaluate(&self.memory, self.relative_base);
-                let y = y.evaluate(&self.memory, self.relative_base);
+                let x = self.read(&x)?;
+                let y = self.read(&y)?;
+                let position = self.address_of(&position)?;
                 if x == y {
-                    self.write_memory(position, 1);
+                    self.write_memory(position, 1)?;
                 } else {
-                    self.write_memory(position, 0);
+                    self.write_memory(position, 0)?;
                 }
-                self.instruction_pointer += 4;
+                self.instruction_pointer += length;
             }, 
             SetRelativeBase{offset} => {
-                self.relative_base += offset.evaluate(&self.memory, self.relative_base) as usize;
-                self.instruction_pointer += 2;
+                let offset = self.read(&offset)?;
+                self.relative_base = self.relative_base.saturating_add(offset);
+                self.instruction_pointer += length;
             },
             Halt => self.state = IntcodeState::Halted,
-        }     
+        }
+
+        Ok(())
     }
 }
 
@@ -227,13 +842,88 @@ impl IntcodeMachine<IntcodeConsoleInput, IntcodeConsoleOutput> {
 
 impl IntcodeMachine<IntcodePresetInput, IntcodeHistoryOutput> {
     pub fn new_automated_machine(machine_code: &[i64], inputs: &[i64]) -> IntcodeMachine<IntcodePresetInput, IntcodeHistoryOutput> {
-        IntcodeMachine::new(machine_code, IntcodePresetInput::new(inputs), IntcodeHistoryOutput::new())
+        IntcodeMachine::new(machine_code, IntcodePresetInput::new(inputs), IntcodeHistoryOutput::default())
+    }
+
+    // Overrides only change memory, so reset() goes back to machine_code as given
+    pub fn new_with_overrides(machine_code: &[i64], overrides: &[(usize, i64)]) -> Result<IntcodeMachine<IntcodePresetInput, IntcodeHistoryOutput>, IntcodeError> {
+        let mut machine = IntcodeMachine::new_automated_machine(machine_code, &[]);
+        for &(position, value) in overrides {
+            machine.write_memory(position, value)?;
+        }
+        Ok(machine)
     }
 }
 
 impl IntcodeMachine<IntcodeBlockingInput, IntcodeHistoryOutput> {
     pub fn new_blocking_machine(machine_code: &[i64]) -> IntcodeMachine<IntcodeBlockingInput, IntcodeHistoryOutput> {
-        IntcodeMachine::new(machine_code, IntcodeBlockingInput, IntcodeHistoryOutput::new())
+        IntcodeMachine::new(machine_code, IntcodeBlockingInput, IntcodeHistoryOutput::default())
+    }
+}
+
+impl IntcodeMachine<IntcodeQueueInput, IntcodeHistoryOutput> {
+    pub fn new_with_inputs(machine_code: &[i64], inputs: Vec<i64>) -> IntcodeMachine<IntcodeQueueInput, IntcodeHistoryOutput> {
+        IntcodeMachine::new(machine_code, IntcodeQueueInput::new(inputs), IntcodeHistoryOutput::default())
+            .with_input_policy(IntcodeInputPolicy::Pause)
+    }
+}
+
+impl <O: IntcodeOutput> IntcodeMachine<IntcodeQueueInput, O> {
+    pub fn push_input(&mut self, value: i64) {
+        self.input_handler.push(value);
+    }
+
+    pub fn push_inputs<T: IntoIterator<Item = i64>>(&mut self, values: T) {
+        for value in values {
+            self.push_input(value);
+        }
+    }
+
+    pub fn push_ascii_line(&mut self, line: &str) {
+        assert!(line.is_ascii(), "Input line is not ASCII: {:?}", line);
+
+        for byte in line.bytes().chain(std::iter::once(b'\n')) {
+            self.push_input(byte as i64);
+        }
+    }
+
+    pub fn run_interactive(&mut self) -> Result<()> {
+        let stdin = std::io::stdin();
+        self.run_interactive_with(stdin.lock(), std::io::stdout())
+    }
+
+    // Relies on the Pause policy so each prompt is printed before the next line is read.
+    // Returns when the program halts or the input runs out.
+    pub fn run_interactive_with<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> Result<()> {
+        loop {
+            self.try_run()?;
+            write!(output, "{}", self.drain_output_ascii())?;
+            output.flush()?;
+
+            if self.state != IntcodeState::Suspended {
+                return Ok(());
+            }
+
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            let line = line.trim_end_matches(&['\r', '\n'][..]);
+            if line.is_ascii() {
+                self.push_ascii_line(line);
+            } else {
+                writeln!(output, "Input must be ASCII, got {:?}", line)?;
+            }
+        }
+    }
+}
+
+impl <I: IntcodeInput> IntcodeMachine<I, IntcodeCallbackOutput> {
+    // Outputs from now on go to f as they're produced instead of being buffered,
+    // so drain_output, the ASCII helpers and any output limit only see earlier ones
+    pub fn set_output_callback(&mut self, f: Box<dyn FnMut(i64)>) {
+        self.output_handler.sink_mut().set_callback(f);
     }
 }
 
@@ -271,8 +961,6 @@ mod tests {
 
     #[test]
     fn test_chaining() {
-        use std::str::FromStr;
-
         let program = vec![
             3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,
             27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5
@@ -300,54 +988,795 @@ mod tests {
                 amp.input(next_input);
                 amp.run();
     
-                let output = amp.output_handler()
-                    .last_output()
-                    .expect("No output available")
-                    .to_owned();
-    
-                next_input = i64::from_str(&output).unwrap();
+                next_input = amp.last_output().expect("No output available");
             }
             if amplifiers[4].state() == &IntcodeState::Halted {
                 break;
             }
         }
     
-        let last_output = amplifiers[4].output_handler().last_output().unwrap().to_owned();
-        assert_eq!(last_output, "139629729");
+        assert_eq!(amplifiers[4].last_output(), Some(139629729));
     }
 
     #[test]
-    fn test_relative_base_copy_self() {
-        use std::str::FromStr;
+    fn test_run_until_prompt() {
+        let program = vec![104,72,104,105,104,10,3,13,104,33,104,10,99,0];
+        let mut machine = IntcodeMachine::new_blocking_machine(&program);
+
+        assert_eq!(machine.run_until_prompt().unwrap(), "Hi\n");
+        assert_eq!(machine.state(), &IntcodeState::Suspended);
+
+        machine.input(1);
+        assert_eq!(machine.run_until_prompt().unwrap(), "!\n");
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&[104,79,104,75,104,10,104,1000,99]);
+        assert_eq!(machine.run_until_prompt().unwrap(), "OK\n");
+        assert_eq!(machine.ascii_answer(), Some(1000));
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&[104,33,42]);
+        assert_eq!(machine.run_until_prompt(), Err(IntcodeError::InvalidOpcode(42, 2)));
+    }
+
+    #[test]
+    fn test_ascii_exchange() {
+        // Prompts with ">" and echoes each line until it reads one starting with "q"
+        let program = vec![
+            104,62,3,100,1008,100,113,101,1001,100,0,102,1005,101,17,4,102,1008,
+            102,10,103,1005,103,29,3,102,1105,1,12,1005,101,35,1105,1,0,99,
+        ];
+        let mut machine = IntcodeMachine::new_blocking_machine(&program);
+
+        assert_eq!(machine.run_until_prompt().unwrap(), ">");
+        assert_eq!(machine.ascii_exchange("hi").unwrap(), "hi\n>");
+        assert_eq!(machine.ascii_exchange("quit").unwrap(), "");
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+        assert!(machine.ascii_exchange("again").is_err());
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&program);
+        assert_eq!(machine.ascii_exchange("hi").unwrap(), ">hi\n>");
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&[3,0,104,500,99]);
+        assert_eq!(machine.ascii_exchange("").unwrap(), "");
+        assert_eq!(machine.ascii_answer(), Some(500));
+    }
+
+    #[test]
+    fn test_ascii_lines() {
+        let mut machine = IntcodeMachine::new_with_inputs(&[3,20,4,20,1105,1,0], vec![]);
+        machine.push_ascii_line("echo me");
+        machine.run();
+
+        assert_eq!(machine.state(), &IntcodeState::Suspended);
+        assert_eq!(machine.drain_output_ascii(), "echo me\n");
+
+        machine.push_ascii_line("");
+        machine.push_input(1000);
+        machine.run();
+        assert_eq!(machine.drain_output_ascii(), "\n");
+        assert_eq!(machine.ascii_answer(), Some(1000));
+    }
+
+    #[test]
+    fn test_run_interactive_with() {
+        // Prompts with "?" and echoes each line back, halting as soon as it reads a "q"
+        let program = [104,63,104,10,3,100,1008,100,113,101,1005,101,25,4,100,1008,100,10,101,1005,101,0,1105,1,4,99];
+        let mut machine = IntcodeMachine::new_with_inputs(&program, vec![]);
+        let mut output = Vec::new();
+
+        machine.run_interactive_with("north\ntake é\nq\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+        assert_eq!(String::from_utf8(output).unwrap(), "?\nnorth\n?\nInput must be ASCII, got \"take é\"\n");
+
+        let mut machine = IntcodeMachine::new_with_inputs(&program, vec![]);
+        let mut output = Vec::new();
+        machine.run_interactive_with("west".as_bytes(), &mut output).unwrap();
+        assert_eq!(machine.state(), &IntcodeState::Suspended);
+        assert_eq!(String::from_utf8(output).unwrap(), "?\nwest\n?\n");
+    }
+
+    #[test]
+    fn test_run_and_checksum() {
+        let mut original = IntcodeMachine::new_automated_machine(&[1101,1,1,5,99], &[]);
+        let mut padded = IntcodeMachine::new_automated_machine(&[1101,1,1,5,99,0,0,0], &[]);
+        let mut overwritten = IntcodeMachine::new_automated_machine(&[1101,1,1,5,99,7], &[]);
+        let mut different = IntcodeMachine::new_automated_machine(&[1101,1,2,5,99], &[]);
+
+        let checksum = original.run_and_checksum().unwrap();
+        assert_eq!(padded.run_and_checksum().unwrap(), checksum);
+        assert_eq!(overwritten.run_and_checksum().unwrap(), checksum);
+        assert_ne!(different.run_and_checksum().unwrap(), checksum);
+
+        let mut blocked = IntcodeMachine::new_blocking_machine(&[3,0,99]);
+        assert!(blocked.run_and_checksum().is_err());
+    }
+
+    #[test]
+    fn test_read_ascii_output_escaped() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[104,72,104,105,104,27,104,10,104,7,104,19690720,99], &[]);
+        machine.run();
+
+        assert_eq!(machine.read_ascii_output_escaped(), "Hi\\x1b\n\\x07");
+        assert_eq!(machine.ascii_answer(), Some(19690720));
+        assert!(machine.output_handler().buffered().is_empty());
+        assert_eq!(machine.read_ascii_output_escaped(), "");
+    }
+
+    #[test]
+    fn test_find_value() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1,0,0,5,99,7], &[]);
+        assert_eq!(machine.find_value(99), vec![4]);
+        assert_eq!(machine.find_value(42), vec![]);
+
+        machine.run();
+        assert_eq!(machine.find_value(2), vec![5]);
+        assert_eq!(&machine.find_value(0)[..3], &[1, 2, 6]);
+    }
+
+    #[test]
+    fn test_verbose_run() {
+        let program = vec![1,0,0,0,99];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]);
+        machine.set_verbose(true);
+        machine.run();
+
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+        assert_eq!(machine.read_memory_position(0), 2);
+    }
+
+    #[test]
+    fn test_ensure_halted() {
+        let mut machine = IntcodeMachine::new_blocking_machine(&[3,0,99]);
+        assert!(machine.ensure_halted().is_err());
+        machine.run();
+        assert!(machine.ensure_halted().is_err());
+        machine.input(1);
+        machine.run();
+        assert!(machine.ensure_halted().is_ok());
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&[104,1,99]).with_output_limit(Some(0));
+        assert!(machine.try_run().is_err());
+        assert!(machine.ensure_halted().is_err());
+    }
+
+    #[test]
+    fn test_input_log() {
+        let program = vec![3,0,3,1,1,0,1,2,4,2,99];
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&program);
+        machine.enable_input_log();
+        machine.run();
+        machine.input(4);
+        machine.run();
+        machine.input(9);
+        machine.run();
+        assert_eq!(machine.consumed_inputs(), &[4, 9]);
+
+        let mut replay = IntcodeMachine::new_automated_machine(&program, machine.consumed_inputs());
+        replay.enable_input_log();
+        replay.run();
+        assert_eq!(replay.consumed_inputs(), &[4, 9]);
+        assert_eq!(replay.output_handler().buffered(), machine.output_handler().buffered());
+    }
+
+    #[test]
+    fn test_input_log_disabled() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[3,0,99], &[5]);
+        machine.run();
+        assert!(machine.consumed_inputs().is_empty());
+    }
+
+    #[test]
+    fn test_strict_input() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[3,0,3,0,99], &[1]).with_strict_input(true);
+        assert_eq!(machine.try_run(), Err(IntcodeError::InputExhausted(2)));
+        assert_eq!(machine.state(), &IntcodeState::Failed(IntcodeError::InputExhausted(2)));
+        assert_eq!(machine.memory()[0], 1);
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&[3,0,99]).with_strict_input(true);
+        assert!(machine.try_run().is_err());
+        assert_eq!(machine.state(), &IntcodeState::Failed(IntcodeError::InputExhausted(0)));
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&[3,0,99]);
+        assert_eq!(machine.try_run(), Ok(()));
+        assert_eq!(machine.state(), &IntcodeState::Suspended);
+    }
+
+    #[test]
+    fn test_input_policy() {
+        let program = [3,20,4,20,3,20,4,20,99];
+
+        let mut machine = IntcodeMachine::new_with_inputs(&program, vec![6]).with_input_policy(IntcodeInputPolicy::Error);
+        assert_eq!(machine.try_run(), Err(IntcodeError::InputExhausted(4)));
+        assert_eq!(machine.drain_output(), vec![6]);
+
+        let mut machine = IntcodeMachine::new_with_inputs(&program, vec![6]).with_input_policy(IntcodeInputPolicy::Default(-1));
+        assert_eq!(machine.step_until_event(), IntcodeEvent::Produced(6));
+        assert!(!machine.will_block());
+        assert_eq!(machine.resume(), IntcodeEvent::Halted);
+        assert_eq!(machine.drain_output(), vec![6, -1]);
+
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]).with_input_policy(IntcodeInputPolicy::Pause);
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Suspended);
+    }
+
+    #[test]
+    fn test_output_limit() {
+        let program = vec![104,1,1105,1,0];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]).with_output_limit(Some(5));
+
+        assert_eq!(machine.try_run(), Err(IntcodeError::OutputLimitExceeded(5)));
+        assert_eq!(machine.state(), &IntcodeState::Failed(IntcodeError::OutputLimitExceeded(5)));
+        assert_eq!(machine.output_handler().buffered().len(), 5);
+    }
+
+    #[test]
+    fn test_run_for() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1101,1,1,20,1001,20,1,20,4,20,99], &[]);
+
+        assert_eq!(machine.run_for(1), Ok(IntcodeState::Running));
+        assert_eq!(machine.program_counter(), 4);
+        assert_eq!(machine.run_for(10), Ok(IntcodeState::Halted));
+        assert_eq!(machine.drain_output(), vec![3]);
+    }
+
+    #[test]
+    fn test_drain_frees_output_limit() {
+        let program = [104,1,104,2,3,20,104,3,99];
+        let mut machine = IntcodeMachine::new_blocking_machine(&program).with_output_limit(Some(2));
+        machine.run();
+        assert_eq!(machine.drain_output(), vec![1, 2]);
+
+        machine.input(0);
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+        assert_eq!(machine.last_output(), Some(3));
+    }
+
+    #[test]
+    fn test_output_limit_not_reached() {
+        let program = vec![104,1,104,2,99];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]).with_output_limit(Some(2));
+        machine.run();
+
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+    }
+
+    #[test]
+    fn test_try_run_errors() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1,0,0,0,42], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::InvalidOpcode(42, 4)));
+        assert_eq!(machine.state(), &IntcodeState::Failed(IntcodeError::InvalidOpcode(42, 4)));
+        assert_eq!(machine.try_run(), Err(IntcodeError::InvalidOpcode(42, 4)));
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[1105,1,5000], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::PcOutOfBounds(0)));
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[1,0,0,0,301,0,99], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::InvalidParameterMode(3, 4)));
+
+        assert!(helpers::run_to_outputs(&[104,1,77], &[]).is_err());
+    }
+
+    #[test]
+    fn test_current_category() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1101,1,1,20,3,21,104,0,1105,0,0,109,1,99], &[5]);
+        let mut categories = Vec::new();
+        while machine.state() != &IntcodeState::Halted {
+            categories.push(machine.current_category().unwrap());
+            machine.run_next_instruction().unwrap();
+        }
+
+        use IntcodeCategory::*;
+        assert_eq!(categories, vec![Arithmetic, IO, IO, ControlFlow, RelativeBase, Halt]);
+        assert!(IntcodeMachine::new_automated_machine(&[42], &[]).current_category().is_err());
+    }
+
+    #[test]
+    fn test_instruction_at() {
+        let machine = IntcodeMachine::new_automated_machine(&[1002,4,3,4,33,104,5,99], &[]);
+
+        assert_eq!(machine.instruction_at(0), Ok(IntcodeInstruction::Multiply {
+            x: IntcodeValue::Position(4),
+            y: IntcodeValue::Immediate(3),
+            position: IntcodeValue::Position(4),
+        }));
+        assert_eq!(machine.instruction_at(5), Ok(IntcodeInstruction::Output { value: IntcodeValue::Immediate(5) }));
+        assert_eq!(machine.instruction_at(7), Ok(IntcodeInstruction::Halt));
+        assert_eq!(machine.instruction_at(4), Err(IntcodeError::InvalidOpcode(33, 4)));
+        assert_eq!(machine.instruction_at(1023), Err(IntcodeError::InvalidOpcode(0, 1023)));
+        assert_eq!(machine.instruction_at(1024), Err(IntcodeError::PcOutOfBounds(1024)));
+    }
+
+    #[test]
+    fn test_run_counting_outputs() {
+        // Outputs the input twice, then again once, then halts
+        let mut machine = IntcodeMachine::new_blocking_machine(&[3,20,4,20,4,20,3,20,4,20,99]);
+
+        assert_eq!(machine.run_counting_outputs(), (0, IntcodeState::Suspended));
+        machine.input(5);
+        assert_eq!(machine.run_counting_outputs(), (2, IntcodeState::Suspended));
+        machine.input(6);
+        assert_eq!(machine.run_counting_outputs(), (1, IntcodeState::Halted));
+    }
+
+    #[test]
+    fn test_step_until_event() {
+        // Reads two values, outputs their sum and product, then halts
+        let program = [3,20,3,21,1,20,21,22,4,22,2,20,21,22,4,22,99];
+        let mut machine = IntcodeMachine::new(&program, IntcodeBlockingInput, IntcodeSinkOutput::new(Vec::new()));
+
+        assert_eq!(machine.step_until_event(), IntcodeEvent::AwaitingInput);
+        assert_eq!(machine.step_until_event(), IntcodeEvent::AwaitingInput);
+        machine.input(3);
+        assert_eq!(machine.step_until_event(), IntcodeEvent::AwaitingInput);
+        machine.input(4);
+        assert_eq!(machine.step_until_event(), IntcodeEvent::Produced(7));
+        assert_eq!(machine.step_until_event(), IntcodeEvent::Produced(12));
+        assert_eq!(machine.step_until_event(), IntcodeEvent::Halted);
+        assert_eq!(machine.output_handler().sink(), &vec![7, 12]);
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[42], &[]);
+        assert_eq!(machine.step_until_event(), IntcodeEvent::Failed(IntcodeError::InvalidOpcode(42, 0)));
+    }
+
+    #[test]
+    fn test_run_with_limit() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1105,1,0], &[]);
+        machine.enable_profiling();
+
+        assert_eq!(machine.run_with_limit(10), Err(IntcodeError::ExecutionLimitExceeded(10)));
+        assert_eq!(machine.total_instructions(), 10);
+        assert_eq!(machine.state(), &IntcodeState::Failed(IntcodeError::ExecutionLimitExceeded(10)));
+
+        let program = [1101,1,1,0,1101,2,2,1,99];
+        assert_eq!(IntcodeMachine::new_automated_machine(&program, &[]).run_with_limit(3), Ok(IntcodeState::Halted));
+        assert!(IntcodeMachine::new_automated_machine(&program, &[]).run_with_limit(2).is_err());
+        assert_eq!(IntcodeMachine::new_blocking_machine(&[3,0,99]).run_with_limit(5), Ok(IntcodeState::Suspended));
+    }
+
+    #[test]
+    fn test_memory_view() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1002,4,3,4,33], &[]);
+        let before = machine.memory().to_vec();
+        assert_eq!(&before[..5], &[1002,4,3,4,33]);
+
+        machine.run();
+        let mut expected = before;
+        expected[4] = 99;
+        assert_eq!(machine.memory(), &expected[..]);
+        assert!(disassembler::disassemble(&machine.memory()[..5]).ends_with("    4: Halt\n"));
+    }
+
+    #[test]
+    fn test_write_to_immediate() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1101,1,1,7,10001,1,2,3,99], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::WriteToImmediate(4)));
+        assert_eq!(machine.read_memory_position(3), 7);
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[103,0,99], &[5]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::WriteToImmediate(0)));
+        assert_eq!(machine.state(), &IntcodeState::Failed(IntcodeError::WriteToImmediate(0)));
+    }
+
+    #[test]
+    fn test_reset() {
+        let program = [3,20,1001,20,5,20,4,20,1101,1,1,0,99];
+        let mut machine = IntcodeMachine::new_with_inputs(&program, vec![3, 4]);
+        machine.enable_input_log();
+        machine.run();
+
+        let first_outputs = machine.output_handler().buffered().to_vec();
+        assert_eq!(machine.drain_output(), vec![8]);
+        assert_eq!(machine.read_memory_position(0), 2);
+
+        machine.reset();
+        assert_eq!(machine.state(), &IntcodeState::Initialized);
+        assert_eq!(machine.read_memory_position(0), 3);
+        assert_eq!(machine.read_memory_position(20), 0);
+        assert!(machine.consumed_inputs().is_empty());
+        assert!(machine.output_handler().buffered().is_empty());
+
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Suspended);
+
+        machine.push_input(3);
+        machine.run();
+        assert_eq!(machine.output_handler().buffered(), &first_outputs[..]);
+        assert_eq!(machine.drain_output(), vec![8]);
+        assert_eq!(machine.consumed_inputs(), &[3]);
+    }
 
+    #[test]
+    fn test_jump_on_negative() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1105,-1,6,104,0,99,104,1,99], &[]);
+        machine.run();
+        assert_eq!(machine.drain_output(), vec![1]);
+    }
+
+    #[test]
+    fn test_invalid_jump_target() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[104,1,1105,1,-3,99], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::PcOutOfBounds(2)));
+        assert_eq!(machine.program_counter(), 2);
+        assert_eq!(machine.drain_output(), vec![1]);
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[104,1,1106,0,5000,99], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::PcOutOfBounds(2)));
+        assert_eq!(machine.state(), &IntcodeState::Failed(IntcodeError::PcOutOfBounds(2)));
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[1106,0,1023,99], &[]);
+        machine.write_memory(1023, 99).unwrap();
+        assert_eq!(machine.try_run(), Ok(()));
+    }
+
+    #[test]
+    fn test_decode_at() {
+        use IntcodeValue::*;
+
+        let machine = IntcodeMachine::new_automated_machine(&[1002,4,3,4,33,204,-2,99], &[]);
+        assert_eq!(machine.decode_at(0), Ok(IntcodeInstruction::Multiply{x: Position(4), y: Immediate(3), position: Position(4)}));
+        assert_eq!(machine.decode_at(5), Ok(IntcodeInstruction::Output{value: Relative(-2)}));
+        assert_eq!(machine.decode_at(7), Ok(IntcodeInstruction::Halt));
+        assert_eq!(machine.decode_at(4), Err(IntcodeError::InvalidOpcode(33, 4)));
+
+        let mut program = vec![0; 1024];
+        program[1021..].copy_from_slice(&[1101, 1, 2]);
+        let machine = IntcodeMachine::new_automated_machine(&program, &[]);
+        assert_eq!(machine.decode_at(1021), Err(IntcodeError::TruncatedInstruction(1021)));
+        assert_eq!(machine.decode_at(1024), Err(IntcodeError::PcOutOfBounds(1024)));
+        assert!(machine.instruction_at(1021).is_ok());
+    }
+
+    #[test]
+    fn test_output_callback() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let program = [104,1,104,2,104,3,1101,20,22,9,4,9,99];
+
+        let total = Rc::new(Cell::new(0));
+        let sum = Rc::clone(&total);
+        let mut machines: Vec<_> = (0..2)
+            .map(|_| IntcodeMachine::new_automated_machine(&program, &[]).with_output_handler(IntcodeCallbackOutput::default()))
+            .collect();
+        machines[1].set_output_callback(Box::new(move |value| sum.set(sum.get() + value)));
+        let mut machines = machines.into_iter();
+
+        let mut buffering = machines.next().unwrap();
+        buffering.run();
+        assert_eq!(buffering.drain_output(), vec![1, 2, 3, 42]);
+
+        let mut streaming = machines.next().unwrap().with_output_limit(Some(1));
+        assert_eq!(streaming.run_counting_outputs(), (4, IntcodeState::Halted));
+        assert!(streaming.drain_output().is_empty());
+        assert_eq!(total.get(), 48);
+
+        let count = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&count);
+        let mut machine = IntcodeMachine::new_blocking_machine(&[104,5,3,0,104,6,99])
+            .with_output_callback(move |_| counter.set(counter.get() + 1));
+        machine.run();
+        machine.input(0);
+        machine.run();
+        assert_eq!(count.get(), 2);
+        assert!(machine.drain_output().is_empty());
+    }
+
+    #[test]
+    fn test_push_inputs() {
+        let mut machine = IntcodeMachine::new_with_inputs(&[3,20,4,20,3,20,4,20,3,20,4,20,99], vec![]);
+        machine.push_inputs(vec![7, -1]);
+        machine.push_inputs([5].iter().copied());
+
+        machine.step_until_event();
+        assert_eq!(machine.read_memory_position(20), 7);
+
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+        assert_eq!(machine.drain_output(), vec![7, -1, 5]);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let program = [1,0,0,0,99];
+        let mut machine = IntcodeMachine::new_with_overrides(&program, &[(1, 4), (2, 4), (2000, 7)]).unwrap();
+        assert_eq!(machine.read_memory_position(2000), 7);
+
+        machine.run();
+        assert_eq!(machine.read_memory_position(0), 198);
+
+        machine.reset();
+        assert_eq!(&machine.memory()[..5], &program);
+        assert_eq!(machine.read_memory_position(2000), 0);
+
+        machine.run();
+        assert_eq!(machine.read_memory_position(0), 2);
+    }
+
+    #[test]
+    fn test_write_trace() {
+        // Rewrites the Add at 4 into a Multiply before running it
+        let program = [1101,1,1101,4,1101,3,4,9,99,0];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]);
+        assert!(machine.write_trace().is_empty());
+
+        machine.enable_write_trace();
+        machine.run();
+
+        let write = |pc, address, old_value, new_value| IntcodeWriteEvent { pc, address, old_value, new_value };
+        assert_eq!(machine.write_trace(), &[write(0, 4, 1101, 1102), write(4, 9, 0, 12)]);
+        assert_eq!(machine.read_memory_position(9), 12);
+    }
+
+    #[test]
+    fn test_uninitialized_reads() {
+        // Reads 500 before and after writing it, then 600 relative to a base of 100
+        let program = [4,500,1101,7,0,500,4,500,109,100,204,500,99];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]);
+        machine.run();
+        assert!(machine.uninitialized_reads().is_empty());
+
+        machine.reset();
+        machine.enable_uninitialized_read_warnings();
+        machine.run();
+        assert_eq!(machine.uninitialized_reads(), &[(0, 500), (10, 600)]);
+        assert_eq!(machine.drain_output(), vec![0, 7, 0]);
+
+        // Cells inside the program count as initialized even if never written
+        let mut machine = IntcodeMachine::new_automated_machine(&[4,2,99], &[]);
+        machine.enable_uninitialized_read_warnings();
+        machine.run();
+        assert!(machine.uninitialized_reads().is_empty());
+    }
+
+    #[test]
+    fn test_profiling() {
+        let program = [1001,14,1,14,4,14,1007,14,3,15,1005,15,0,99,0,0];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]);
+        machine.run_until_output();
+        assert_eq!(machine.total_instructions(), 0);
+
+        machine.enable_profiling();
+        machine.run();
+
+        let counts = machine.instruction_counts();
+        assert_eq!(counts.get("Add"), Some(&2));
+        assert_eq!(counts.get("Out"), Some(&2));
+        assert_eq!(counts.get("Lst"), Some(&3));
+        assert_eq!(counts.get("JmT"), Some(&3));
+        assert_eq!(counts.get("Halt"), Some(&1));
+        assert_eq!(counts.get("Mul"), None);
+        assert_eq!(machine.total_instructions(), 11);
+    }
+
+    #[test]
+    fn test_negative_relative_base_adjustment() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[109,5,109,-3,204,-2,99], &[]);
+        machine.run();
+        assert_eq!(machine.relative_base(), 2);
+        assert_eq!(machine.drain_output(), vec![109]);
+    }
+
+    #[test]
+    fn test_negative_relative_address() {
+        // A negative base is fine until something reads relative to it
+        let mut machine = IntcodeMachine::new_automated_machine(&[109,-5,204,2,99], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::InvalidAddress(-3, 2)));
+        assert_eq!(machine.relative_base(), -5);
+
+        let program = [109,-5,21101,1,1,1,99];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::InvalidAddress(-4, 2)));
+        assert_eq!(machine.memory()[..program.len()], program);
+    }
+
+    #[test]
+    fn test_corrupt_programs_fail() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1102,4_000_000_000,4_000_000_000,0,99], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::ArithmeticOverflow(0)));
+
+        // Running out of preset inputs waits for more rather than panicking
+        let mut machine = IntcodeMachine::new_automated_machine(&[3,0,3,0,99], &[1]);
+        assert_eq!(machine.try_run(), Ok(()));
+        assert_eq!(machine.state(), &IntcodeState::Suspended);
+    }
+
+    #[test]
+    fn test_write_outside_memory() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[1,0,0,-1,99], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::InvalidAddress(-1, 0)));
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[1101,1,1,1_000_000_000_000,99], &[]);
+        assert_eq!(machine.try_run(), Err(IntcodeError::InvalidAddress(1_000_000_000_000, 0)));
+        assert!(IntcodeMachine::new_with_overrides(&[99], &[(MAX_MEMORY, 1)]).is_err());
+    }
+
+    #[test]
+    fn test_step() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[109,4,1101,2,3,20,204,16,99], &[]);
+
+        assert_eq!(machine.step(), IntcodeEvent::Running);
+        assert_eq!((machine.program_counter(), machine.relative_base()), (2, 4));
+        assert_eq!(machine.step(), IntcodeEvent::Running);
+        assert_eq!(machine.step(), IntcodeEvent::Produced(5));
+        assert_eq!(machine.step(), IntcodeEvent::Halted);
+        assert_eq!(machine.program_counter(), 8);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        // Counts memory[14] up from 0 to 3, outputting it each time around the loop
+        let program = [1001,14,1,14,4,14,1007,14,3,15,1005,15,0,99,0,0];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]);
+        machine.add_breakpoint(4);
+
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Breakpoint(4));
+        assert_eq!(machine.program_counter(), 4);
+        assert_eq!(machine.read_memory_position(14), 1);
+        assert_eq!(machine.last_output(), None);
+
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Breakpoint(4));
+        assert_eq!(machine.read_memory_position(14), 2);
+        assert_eq!(machine.last_output(), Some(1));
+
+        machine.remove_breakpoint(4);
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+        assert_eq!(machine.drain_output(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_memory_growth() {
+        let mut machine = IntcodeMachine::new_blocking_machine(&[99]);
+        assert_eq!(machine.read_memory_position(20_000), 0);
+
+        machine.write_memory(10_000, 42).unwrap();
+        assert_eq!(machine.read_memory_position(10_000), 42);
+        assert_eq!(machine.read_memory_position(9_999), 0);
+        assert_eq!(machine.memory().len(), 10_001);
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[21101,3,4,5000,204,5000,4,3000,99], &[]);
+        machine.run();
+        assert_eq!(machine.drain_output(), vec![7, 0]);
+        assert_eq!(machine.read_memory_position(5000), 7);
+    }
+
+    #[test]
+    fn test_output_buffer() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[104,3,104,-4,3,20,4,20,99], &[5]);
+        assert_eq!(machine.last_output(), None);
+
+        machine.run();
+        assert_eq!(machine.last_output(), Some(5));
+        assert_eq!(machine.drain_output(), vec![3, -4, 5]);
+        assert_eq!(machine.drain_output(), Vec::<i64>::new());
+        assert_eq!(machine.last_output(), None);
+        assert!(machine.output_handler().buffered().is_empty());
+
+        let mut machine = IntcodeMachine::new(&[104,8,99], IntcodeBlockingInput, IntcodeSinkOutput::new(Vec::new()));
+        machine.run();
+        assert_eq!(machine.last_output(), Some(8));
+    }
+
+    #[test]
+    fn test_queued_inputs() {
+        let mut machine = IntcodeMachine::new_with_inputs(&[3,20,4,20,3,20,4,20,99], vec![6]);
+
+        assert_eq!(machine.step_until_event(), IntcodeEvent::Produced(6));
+        assert_eq!(machine.step_until_event(), IntcodeEvent::AwaitingInput);
+        assert_eq!(machine.resume(), IntcodeEvent::AwaitingInput);
+
+        machine.push_input(-2);
+        assert_eq!(machine.resume(), IntcodeEvent::Halted);
+        assert_eq!(machine.output_handler().buffered(), &[6, -2]);
+
+        let mut machine = IntcodeMachine::new_with_inputs(&[3,20,99], vec![]);
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Suspended);
+    }
+
+    #[test]
+    fn test_resume() {
+        let program = [3,20,4,20,4,20,3,20,99];
+        let mut machine = IntcodeMachine::new_blocking_machine(&program);
+
+        assert_eq!(machine.step_until_event(), IntcodeEvent::AwaitingInput);
+        machine.input(9);
+        assert_eq!(machine.step_until_event(), IntcodeEvent::Produced(9));
+        assert_eq!(machine.resume(), IntcodeEvent::AwaitingInput);
+        assert_eq!(machine.output_handler().buffered(), &[9, 9]);
+        machine.input(1);
+        assert_eq!(machine.resume(), IntcodeEvent::Halted);
+    }
+
+    #[test]
+    fn test_fork_on_inputs() {
+        // Doubles every input, forever
+        let machine = IntcodeMachine::new_blocking_machine(&[3,100,1002,100,2,100,4,100,1105,1,0]);
+
+        let forks = machine.fork_on_inputs(&[1, 2, 3]);
+        let outputs: Vec<(i64, Option<i64>)> = forks.iter().map(|(input, _, output)| (*input, *output)).collect();
+        assert_eq!(outputs, vec![(1, Some(2)), (2, Some(4)), (3, Some(6))]);
+
+        let (_, fork, _) = &forks[2];
+        let next: Vec<Option<i64>> = fork.fork_on_inputs(&[10]).into_iter().map(|(_, _, output)| output).collect();
+        assert_eq!(next, vec![Some(20)]);
+        assert_eq!(machine.state(), &IntcodeState::Initialized);
+
+        let halted = IntcodeMachine::new_blocking_machine(&[99]);
+        assert_eq!(halted.fork_on_inputs(&[1])[0].2, None);
+    }
+
+    #[test]
+    fn test_will_block() {
+        let program = vec![104,1,3,0,99];
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&program);
+        assert!(!machine.will_block());
+        machine.run();
+        assert!(machine.will_block());
+        machine.input(5);
+        assert!(!machine.will_block());
+
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[5]);
+        machine.run_next_instruction().unwrap();
+        assert!(!machine.will_block());
+    }
+
+    #[test]
+    fn test_take_output_chunks() {
+        let program = vec![104,1,104,2,104,3,104,4,104,5,3,0,104,6,99];
+        let mut machine = IntcodeMachine::new_blocking_machine(&program);
+        machine.run();
+
+        assert!(machine.take_output_chunks(0).is_empty());
+        assert_eq!(machine.take_output_chunks(3), vec![vec![1, 2, 3]]);
+        assert_eq!(machine.output_handler().buffered(), &[4, 5]);
+
+        machine.input(0);
+        machine.run();
+        assert_eq!(machine.take_output_chunks(3), vec![vec![4, 5, 6]]);
+        assert!(machine.take_output_chunks(3).is_empty());
+    }
+
+    #[test]
+    fn test_relative_base_copy_self() {
         let program = vec![109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
-        let result = helpers::process_input(&program, &[]).into_iter()
-            .flat_map(|output| i64::from_str(&output))
-            .collect::<Vec<i64>>();
+        let result = helpers::process_input(&program, &[]);
      
         assert_eq!(&result, &program);
     }
 
     #[test]
-    fn test_big_numbers_1() {
-        use std::str::FromStr;
+    fn test_relative_mode_writes() {
+        // Set the relative base to 100 then add, multiply, compare and input relative to it
+        let program = [109,100, 21101,3,4,2, 4,102, 21202,2,5,3, 4,103, 21107,1,2,4, 4,104, 203,5, 4,105, 99];
+        let result = helpers::run_to_outputs(&program, &[-9]).unwrap();
+        assert_eq!(result, vec![7, 35, 1, -9]);
 
+        let mut machine = IntcodeMachine::new_blocking_machine(&[109,10,203,-1,4,9,99]);
+        machine.run();
+        machine.input(42);
+        machine.run();
+        assert_eq!(machine.output_handler().buffered(), &[42]);
+    }
+
+    #[test]
+    fn test_big_numbers_1() {
         let program = vec![1102,34915192,34915192,7,4,7,99,0];
-        let result = helpers::process_input(&program, &[]).into_iter()
-            .flat_map(|output| i64::from_str(&output))
-            .collect::<Vec<i64>>();
+        let result = helpers::process_input(&program, &[]);
      
         assert_eq!(&result, &[1219070632396864]);
     }
 
     #[test]
     fn test_big_numbers_2() {
-        use std::str::FromStr;
-
         let program = vec![104,1125899906842624,99];
-        let result = helpers::process_input(&program, &[]).into_iter()
-            .flat_map(|output| i64::from_str(&output))
-            .collect::<Vec<i64>>();
+        let result = helpers::process_input(&program, &[]);
      
         assert_eq!(&result, &[1125899906842624]);
     }
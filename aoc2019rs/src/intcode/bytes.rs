@@ -0,0 +1,387 @@
+use anyhow::{anyhow, bail, Result};
+
+use std::convert::TryInto;
+
+use crate::intcode::{IntcodeMachine, IntcodeState, IntcodeError, IntcodeInputPolicy, IntcodeOutput, IntcodeQueuedInput, IntcodeHistoryOutput, MAX_MEMORY};
+
+/*
+    Layout (all integers little-endian, u64 unless noted):
+    state (u8) [error tag (u8), two error values if Failed] [address if at a breakpoint]
+    instruction pointer
+    relative base
+    program length, program (i64)
+    memory length, used length, memory up to the last non-zero cell (i64)
+    queued input count, queued inputs (i64)
+    buffered output count, buffered outputs (i64)
+    outputs produced since the last reset
+    has output limit (u8) [limit]
+    input policy (u8) [default (i64)]
+    has ASCII answer (u8) [answer (i64)]
+    has input log (u8) [log length, log (i64)]
+    verbose (u8)
+    breakpoint count, breakpoints
+
+    Profiling, the write trace and uninitialized read warnings aren't kept, so a
+    restored machine starts with them off.
+*/
+
+impl<I: IntcodeQueuedInput> IntcodeMachine<I, IntcodeHistoryOutput> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let used = self.memory.iter().rposition(|&value| value != 0).map_or(0, |last| last + 1);
+        let mut bytes = Vec::with_capacity(8 * (self.program.len() + used + 16));
+
+        match &self.state {
+            IntcodeState::Initialized => bytes.push(0),
+            IntcodeState::Running => bytes.push(1),
+            IntcodeState::Suspended => bytes.push(2),
+            IntcodeState::Halted => bytes.push(3),
+            IntcodeState::Failed(error) => {
+                bytes.push(4);
+                write_error(&mut bytes, error);
+            },
+            IntcodeState::Breakpoint(address) => {
+                bytes.push(5);
+                write_u64(&mut bytes, *address as u64);
+            },
+        }
+
+        write_u64(&mut bytes, self.instruction_pointer as u64);
+        write_u64(&mut bytes, self.relative_base as u64);
+        write_values(&mut bytes, &self.program);
+        write_u64(&mut bytes, self.memory.len() as u64);
+        write_values(&mut bytes, &self.memory[..used]);
+
+        write_values(&mut bytes, &self.input_handler.queued());
+        write_values(&mut bytes, self.output_handler.buffered());
+        write_u64(&mut bytes, self.produced_outputs as u64);
+
+        match self.max_buffered_outputs {
+            Some(limit) => {
+                bytes.push(1);
+                write_u64(&mut bytes, limit as u64);
+            },
+            None => bytes.push(0),
+        }
+
+        match self.input_policy {
+            IntcodeInputPolicy::Block => bytes.push(0),
+            IntcodeInputPolicy::Pause => bytes.push(1),
+            IntcodeInputPolicy::Error => bytes.push(2),
+            IntcodeInputPolicy::Default(value) => {
+                bytes.push(3);
+                bytes.extend_from_slice(&value.to_le_bytes());
+            },
+        }
+
+        match self.ascii_answer {
+            Some(answer) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&answer.to_le_bytes());
+            },
+            None => bytes.push(0),
+        }
+
+        match &self.input_log {
+            Some(log) => {
+                bytes.push(1);
+                write_values(&mut bytes, log);
+            },
+            None => bytes.push(0),
+        }
+
+        bytes.push(self.verbose as u8);
+
+        write_u64(&mut bytes, self.breakpoints.len() as u64);
+        for &address in &self.breakpoints {
+            write_u64(&mut bytes, address as u64);
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+
+        let state = match reader.read_u8()? {
+            0 => IntcodeState::Initialized,
+            1 => IntcodeState::Running,
+            2 => IntcodeState::Suspended,
+            3 => IntcodeState::Halted,
+            4 => IntcodeState::Failed(reader.read_error()?),
+            5 => IntcodeState::Breakpoint(reader.read_u64()? as usize),
+            tag => bail!("Invalid machine state tag: {}", tag),
+        };
+
+        let instruction_pointer = reader.read_u64()? as usize;
+        let relative_base = reader.read_u64()? as i64;
+        let program = reader.read_values()?;
+        let memory_length = reader.read_u64()? as usize;
+        let mut memory = reader.read_values()?;
+        if memory_length > MAX_MEMORY || memory.len() > memory_length {
+            bail!("{} memory cells stored for a memory of {}", memory.len(), memory_length);
+        }
+        memory.resize(memory_length, 0);
+
+        let queued = reader.read_values()?;
+        let queued_count = queued.len();
+        let input_handler = I::from_queued(queued)
+            .ok_or_else(|| anyhow!("Input handler can't hold {} queued inputs", queued_count))?;
+
+        let output_handler = IntcodeHistoryOutput::new(reader.read_values()?);
+        let produced_outputs = reader.read_u64()? as usize;
+
+        let max_buffered_outputs = match reader.read_u8()? {
+            0 => None,
+            _ => Some(reader.read_u64()? as usize),
+        };
+
+        let input_policy = match reader.read_u8()? {
+            0 => IntcodeInputPolicy::Block,
+            1 => IntcodeInputPolicy::Pause,
+            2 => IntcodeInputPolicy::Error,
+            3 => IntcodeInputPolicy::Default(reader.read_i64()?),
+            tag => bail!("Invalid input policy tag: {}", tag),
+        };
+
+        let ascii_answer = match reader.read_u8()? {
+            0 => None,
+            _ => Some(reader.read_i64()?),
+        };
+
+        let input_log = match reader.read_u8()? {
+            0 => None,
+            _ => Some(reader.read_values()?),
+        };
+
+        let verbose = reader.read_u8()? != 0;
+
+        let breakpoint_count = reader.read_u64()?;
+        let breakpoints = (0..breakpoint_count)
+            .map(|_| reader.read_u64().map(|address| address as usize))
+            .collect::<Result<_>>()?;
+
+        if reader.offset != bytes.len() {
+            bail!("{} unexpected trailing bytes", bytes.len() - reader.offset);
+        }
+
+        Ok(Self {
+            state,
+            instruction_pointer,
+            relative_base,
+            program,
+            memory,
+            produced_outputs,
+            input_log,
+            max_buffered_outputs,
+            input_policy,
+            ascii_answer,
+            verbose,
+            profile: None,
+            write_trace: None,
+            read_watch: None,
+            breakpoints,
+            input_handler,
+            output_handler,
+        })
+    }
+}
+
+fn write_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_error(bytes: &mut Vec<u8>, error: &IntcodeError) {
+    let (tag, first, second) = match error {
+        IntcodeError::OutputLimitExceeded(limit) => (0, *limit as u64, 0),
+        IntcodeError::InvalidParameterMode(mode, pc) => (1, *mode as u64, *pc as u64),
+        IntcodeError::InvalidOpcode(opcode, pc) => (2, *opcode as u64, *pc as u64),
+        IntcodeError::PcOutOfBounds(pc) => (3, *pc as u64, 0),
+        IntcodeError::InputUnderflow => (4, 0, 0),
+        IntcodeError::ExecutionLimitExceeded(limit) => (5, *limit, 0),
+        IntcodeError::WriteToImmediate(pc) => (6, *pc as u64, 0),
+        IntcodeError::TruncatedInstruction(pc) => (7, *pc as u64, 0),
+        IntcodeError::InvalidAddress(address, pc) => (8, *address as u64, *pc as u64),
+        IntcodeError::ArithmeticOverflow(pc) => (9, *pc as u64, 0),
+        IntcodeError::InputExhausted(pc) => (10, *pc as u64, 0),
+    };
+
+    bytes.push(tag);
+    write_u64(bytes, first);
+    write_u64(bytes, second);
+}
+
+fn write_values(bytes: &mut Vec<u8>, values: &[i64]) {
+    write_u64(bytes, values.len() as u64);
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl ByteReader<'_> {
+    fn take(&mut self, count: usize) -> Result<&[u8]> {
+        let end = self.offset.checked_add(count)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("Unexpected end of machine data at byte {}", self.offset))?;
+
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn read_i64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn read_error(&mut self) -> Result<IntcodeError> {
+        let tag = self.read_u8()?;
+        let first = self.read_u64()?;
+        let second = self.read_u64()? as usize;
+
+        Ok(match tag {
+            0 => IntcodeError::OutputLimitExceeded(first as usize),
+            1 => IntcodeError::InvalidParameterMode(first as usize, second),
+            2 => IntcodeError::InvalidOpcode(first as i64, second),
+            3 => IntcodeError::PcOutOfBounds(first as usize),
+            4 => IntcodeError::InputUnderflow,
+            5 => IntcodeError::ExecutionLimitExceeded(first),
+            6 => IntcodeError::WriteToImmediate(first as usize),
+            7 => IntcodeError::TruncatedInstruction(first as usize),
+            8 => IntcodeError::InvalidAddress(first as i64, second),
+            9 => IntcodeError::ArithmeticOverflow(first as usize),
+            10 => IntcodeError::InputExhausted(first as usize),
+            _ => bail!("Invalid machine error tag: {}", tag),
+        })
+    }
+
+    fn read_values(&mut self) -> Result<Vec<i64>> {
+        let count = self.read_u64()? as usize;
+        (0..count).map(|_| self.read_i64()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::intcode::{IntcodeBlockingInput, IntcodeQueueInput};
+
+    type BlockingMachine = IntcodeMachine<IntcodeBlockingInput, IntcodeHistoryOutput>;
+
+    #[test]
+    fn test_round_trip() {
+        let program = [104,7,3,20,4,20,99];
+        let mut machine = IntcodeMachine::new_blocking_machine(&program).with_output_limit(Some(10));
+        machine.enable_input_log();
+        machine.run();
+
+        let mut restored = BlockingMachine::from_bytes(&machine.to_bytes()).unwrap();
+        assert_eq!(restored.to_bytes(), machine.to_bytes());
+        assert_eq!(restored.state(), &IntcodeState::Suspended);
+        assert_eq!(restored.memory(), machine.memory());
+        assert_eq!(restored.output_handler().buffered(), &[7]);
+        assert_eq!(restored.last_output(), Some(7));
+
+        restored.input(-3);
+        restored.run();
+        assert_eq!(restored.state(), &IntcodeState::Halted);
+        assert_eq!(restored.output_handler().buffered(), &[7, -3]);
+        assert_eq!(restored.consumed_inputs(), &[-3]);
+
+        restored.reset();
+        assert_eq!(restored.memory(), IntcodeMachine::new_blocking_machine(&program).memory());
+    }
+
+    #[test]
+    fn test_breakpoint_round_trip() {
+        let mut machine = IntcodeMachine::new_blocking_machine(&[104,7,104,8,99]);
+        machine.add_breakpoint(2);
+        machine.run();
+
+        let mut restored = BlockingMachine::from_bytes(&machine.to_bytes()).unwrap();
+        assert_eq!(restored.state(), &IntcodeState::Breakpoint(2));
+        assert_eq!(restored.to_bytes(), machine.to_bytes());
+
+        restored.run();
+        assert_eq!(restored.state(), &IntcodeState::Halted);
+        assert_eq!(restored.drain_output(), vec![7, 8]);
+    }
+
+    #[test]
+    fn test_failed_round_trip() {
+        let mut machine = IntcodeMachine::new_blocking_machine(&[104,1,1105,1,0]).with_output_limit(Some(3));
+        assert!(machine.try_run().is_err());
+
+        let restored = BlockingMachine::from_bytes(&machine.to_bytes()).unwrap();
+        assert_eq!(restored.state(), &IntcodeState::Failed(IntcodeError::OutputLimitExceeded(3)));
+
+        let mut machine = IntcodeMachine::new_blocking_machine(&[3,0,99]).with_strict_input(true);
+        assert!(machine.try_run().is_err());
+
+        let restored = BlockingMachine::from_bytes(&machine.to_bytes()).unwrap();
+        assert_eq!(restored.state(), &IntcodeState::Failed(IntcodeError::InputExhausted(0)));
+        assert_eq!(restored.to_bytes(), machine.to_bytes());
+
+        let machine = IntcodeMachine::new_blocking_machine(&[3,0,99]).with_input_policy(IntcodeInputPolicy::Default(-9));
+        let mut restored = BlockingMachine::from_bytes(&machine.to_bytes()).unwrap();
+        restored.run();
+        assert_eq!(restored.read_memory_position(0), -9);
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        let bytes = IntcodeMachine::new_blocking_machine(&[99]).to_bytes();
+
+        assert!(BlockingMachine::from_bytes(&[]).is_err());
+        assert!(BlockingMachine::from_bytes(&[9]).is_err());
+        assert!(BlockingMachine::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(BlockingMachine::from_bytes(&[&bytes[..], &[0]].concat()).is_err());
+    }
+
+    #[test]
+    fn test_queued_inputs_round_trip() {
+        let program = [3,20,4,20,3,20,4,20,99];
+        let mut machine = IntcodeMachine::new_with_inputs(&program, vec![5]);
+        machine.run();
+        machine.push_inputs(vec![6, 7]);
+
+        let bytes = machine.to_bytes();
+        let mut restored: IntcodeMachine<IntcodeQueueInput, _> = IntcodeMachine::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+
+        restored.run();
+        assert_eq!(restored.state(), &IntcodeState::Halted);
+        assert_eq!(restored.drain_output(), vec![5, 6]);
+        assert_eq!(restored.input_handler().queued(), vec![7]);
+
+        // A blocking machine has nowhere to put them
+        assert!(BlockingMachine::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_compact_memory() {
+        // Only the cells up to the 5 written at 20 are stored, not all 1024
+        let mut machine = IntcodeMachine::new_blocking_machine(&[1101,2,3,20,99]);
+        machine.run();
+        machine.set_verbose(true);
+
+        let bytes = machine.to_bytes();
+        assert!(bytes.len() < 8 * 64, "{} bytes", bytes.len());
+
+        let restored = BlockingMachine::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.memory(), machine.memory());
+        assert_eq!(restored.to_bytes(), bytes);
+    }
+}
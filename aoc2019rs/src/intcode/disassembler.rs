@@ -0,0 +1,200 @@
+use std::collections::BTreeSet;
+
+use crate::intcode::{IntcodeInstruction, IntcodeValue};
+
+// Stops after Halt, or before the first word that doesn't decode as a complete
+// instruction, so data embedded in the code ends the walk instead of panicking
+pub fn decode_program(program: &[i64]) -> Vec<(usize, IntcodeInstruction)> {
+    let mut result = Vec::new();
+
+    let mut address = 0;
+    while address < program.len() {
+        let instruction = match IntcodeInstruction::decode(program, address) {
+            Ok(instruction) if address + instruction.length() <= program.len() => instruction,
+            _ => break,
+        };
+        let length = instruction.length();
+        let is_halt = instruction == IntcodeInstruction::Halt;
+
+        result.push((address, instruction));
+        if is_halt {
+            break;
+        }
+        address += length;
+    }
+
+    result
+}
+
+// Everything after the last instruction decode_program finds, whether it stopped
+// at a Halt or at a word that isn't an instruction
+pub fn data_section(program: &[i64]) -> &[i64] {
+    let code_length = decode_program(program).last()
+        .map_or(0, |(address, instruction)| address + instruction.length());
+    &program[code_length..]
+}
+
+pub fn opcodes_used(program: &[i64]) -> BTreeSet<i64> {
+    decode_program(program).iter()
+        .map(|(address, _)| program[*address] % 100)
+        .collect()
+}
+
+pub fn uses_relative_mode(program: &[i64]) -> bool {
+    decode_program(program).iter()
+        .any(|(_, instruction)| {
+            matches!(instruction, IntcodeInstruction::SetRelativeBase{..})
+                || instruction.read_operands().into_iter()
+                    .chain(instruction.write_target())
+                    .any(|value| matches!(value, IntcodeValue::Relative(_)))
+        })
+}
+
+pub fn assemble(instructions: &[IntcodeInstruction]) -> Vec<i64> {
+    let mut program: Vec<i64> = instructions.iter().flat_map(IntcodeInstruction::encode).collect();
+
+    if instructions.last() != Some(&IntcodeInstruction::Halt) {
+        program.extend(IntcodeInstruction::Halt.encode());
+    }
+
+    program
+}
+
+// Only the code decode_program finds, without the DATA lines disassemble adds
+pub fn disassemble_to_string(program: &[i64]) -> String {
+    decode_program(program).into_iter()
+        .map(|(address, instruction)| format!("{:>5}: {}\n", address, instruction))
+        .collect()
+}
+
+// Unlike decode_program, keeps going past Halt and lists anything that doesn't
+// decode as a complete instruction as DATA, one word at a time
+pub fn disassemble(program: &[i64]) -> String {
+    let mut result = String::new();
+
+    let mut address = 0;
+    while address < program.len() {
+        match IntcodeInstruction::decode(program, address) {
+            Ok(instruction) if address + instruction.length() <= program.len() => {
+                result.push_str(&format!("{:>5}: {}\n", address, instruction));
+                address += instruction.length();
+            },
+            _ => {
+                result.push_str(&format!("{:>5}: DATA {}\n", address, program[address]));
+                address += 1;
+            },
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::input;
+
+    #[test]
+    fn test_decode_strides() {
+        let addresses: Vec<usize> = decode_program(&[1,0,0,0,3,0,1105,1,0,99,5,5]).into_iter()
+            .map(|(address, _)| address)
+            .collect();
+        assert_eq!(addresses, vec![0, 4, 6, 9]);
+    }
+
+    #[test]
+    fn test_decode_stops_at_data() {
+        let instructions: Vec<IntcodeInstruction> = decode_program(&[1105,1,4,42,99]).into_iter()
+            .map(|(_, instruction)| instruction)
+            .collect();
+        assert_eq!(instructions, vec![IntcodeInstruction::new(1105, &[1,4])]);
+
+        assert_eq!(decode_program(&[1101,2,3,4,-1,99]).len(), 1);
+        assert_eq!(decode_program(&[3101,2,99]).len(), 0);
+        assert_eq!(decode_program(&[1101,2,3]).len(), 0);
+    }
+
+    #[test]
+    fn test_data_section() {
+        assert_eq!(data_section(&[1,0,0,0,99,30,40,50]), &[30, 40, 50]);
+        assert_eq!(data_section(&[1,0,0,0,99]), &[] as &[i64]);
+        assert_eq!(data_section(&[1,0,0,0]), &[] as &[i64]);
+        assert_eq!(data_section(&[1105,1,4,42,99]), &[42, 99]);
+        assert_eq!(data_section(&[-1,99]), &[-1, 99]);
+
+        let program = input::read_input_list_as::<i64>(2, b',').unwrap();
+        let code_length: usize = decode_program(&program).iter().map(|(_, instruction)| instruction.length()).sum();
+        assert_eq!(code_length + data_section(&program).len(), program.len());
+    }
+
+    #[test]
+    fn test_opcodes_used() {
+        let opcodes: Vec<i64> = opcodes_used(&[1002,4,3,4,1105,1,7,204,0,99]).into_iter().collect();
+        assert_eq!(opcodes, vec![2, 4, 5, 99]);
+
+        let program = input::read_input_list_as::<i64>(2, b',').unwrap();
+        let opcodes: Vec<i64> = opcodes_used(&program).into_iter().collect();
+        assert_eq!(opcodes, vec![1, 2, 99]);
+
+        // Day 5 rewrites the word at 6 into an Add before running it, so statically
+        // the walk stops there instead of panicking
+        let program = input::read_input_cached(5).unwrap();
+        let opcodes: Vec<i64> = opcodes_used(&program).into_iter().collect();
+        assert_eq!(opcodes, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_uses_relative_mode() {
+        assert!(uses_relative_mode(&[109,1,204,-1,99]));
+        assert!(uses_relative_mode(&[9,5,99]));
+        assert!(uses_relative_mode(&[21101,1,1,0,99]));
+        assert!(!uses_relative_mode(&[1002,4,3,4,99]));
+        assert!(!uses_relative_mode(&[1,0,0,0,99,209,0]));
+        assert!(uses_relative_mode(&[109,3,1105,1,7,-1,99]));
+        assert!(!uses_relative_mode(&[1001,0,0,0,1105,1,0,-3,209,0]));
+
+        let program = input::read_input_list_as::<i64>(2, b',').unwrap();
+        assert!(!uses_relative_mode(&program));
+    }
+
+    #[test]
+    fn test_assemble() {
+        use crate::intcode::IntcodeValue::*;
+
+        let instructions = [
+            IntcodeInstruction::Input { position: Position(9) },
+            IntcodeInstruction::Multiply { x: Position(9), y: Immediate(2), position: Position(9) },
+            IntcodeInstruction::Output { value: Position(9) },
+        ];
+        assert_eq!(assemble(&instructions), vec![3,9,1002,9,2,9,4,9,99]);
+        assert_eq!(assemble(&[IntcodeInstruction::Halt]), vec![99]);
+        assert_eq!(assemble(&[]), vec![99]);
+
+        let program = input::read_input_list_as::<i64>(2, b',').unwrap();
+        let instructions: Vec<IntcodeInstruction> = decode_program(&program).into_iter()
+            .map(|(_, instruction)| instruction)
+            .collect();
+        let code = assemble(&instructions);
+        assert_eq!(&code[..], &program[..code.len()]);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let text = disassemble(&[1101,2,3,9,99,-7,1,0,77]);
+        let lines: Vec<&str> = text.lines().map(str::trim_start).collect();
+
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("0: "));
+        assert_eq!(&lines[1..], &["4: Halt", "5: DATA -7", "6: DATA 1", "7: DATA 0", "8: DATA 77"]);
+
+        let program = input::read_input_list_as::<i64>(2, b',').unwrap();
+        assert!(disassemble(&program).starts_with(&disassemble_to_string(&program)));
+    }
+
+    #[test]
+    fn test_disassemble_golden() {
+        let program = input::read_input_list_as::<i64>(2, b',').unwrap();
+        let golden = input::read_fixture("day2_disassembly").unwrap();
+        assert_eq!(disassemble_to_string(&program), golden);
+    }
+}
@@ -0,0 +1,36 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntcodeError {
+    OutputLimitExceeded(usize),
+    InvalidParameterMode(usize, usize),
+    InvalidOpcode(i64, usize),
+    PcOutOfBounds(usize),
+    InputUnderflow,
+    ExecutionLimitExceeded(u64),
+    WriteToImmediate(usize),
+    TruncatedInstruction(usize),
+    InvalidAddress(i64, usize),
+    ArithmeticOverflow(usize),
+    InputExhausted(usize),
+}
+
+impl std::fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use IntcodeError::*;
+
+        match self {
+            OutputLimitExceeded(limit) => write!(f, "Output buffer exceeded {} values", limit),
+            InvalidParameterMode(mode, pc) => write!(f, "Invalid parameter mode {} at {}", mode, pc),
+            InvalidOpcode(opcode, pc) => write!(f, "Invalid instruction {} at {}", opcode, pc),
+            PcOutOfBounds(pc) => write!(f, "Instruction pointer {} out of range", pc),
+            InputUnderflow => write!(f, "Input requested with none available"),
+            ExecutionLimitExceeded(limit) => write!(f, "Still running after {} instructions", limit),
+            WriteToImmediate(pc) => write!(f, "Write to an immediate mode parameter at {}", pc),
+            TruncatedInstruction(pc) => write!(f, "Instruction at {} runs past the end of memory", pc),
+            InvalidAddress(address, pc) => write!(f, "Address {} at {} is outside memory", address, pc),
+            ArithmeticOverflow(pc) => write!(f, "Arithmetic overflow at {}", pc),
+            InputExhausted(pc) => write!(f, "Input at {} ran out under the Error input policy", pc),
+        }
+    }
+}
+
+impl std::error::Error for IntcodeError {}
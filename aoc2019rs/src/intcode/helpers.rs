@@ -1,15 +1,297 @@
-use super::{IntcodeMachine, IntcodeOutput, IntcodeInstruction};
+use anyhow::{anyhow, bail, Result};
 
-pub fn process_input(program: &[i64], inputs: &[i64]) -> Vec<String> {
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use super::{IntcodeMachine, IntcodeEvent, IntcodeInput, IntcodeOutput, IntcodeInstruction, IntcodeState, IntcodeBlockingInput, IntcodePresetInput, IntcodeSinkOutput};
+
+pub fn process_input(program: &[i64], inputs: &[i64]) -> Vec<i64> {
     let mut machine = IntcodeMachine::new_automated_machine(program, inputs);
     machine.run();
     let (_, _, _, output_handler) = machine.teardown();
-    output_handler.history().to_vec()
+    output_handler.into_sink()
 }
 
-pub fn debug_process_input(program: &[i64], inputs: &[i64]) -> (Vec<IntcodeInstruction>, Vec<String>) {
+pub fn debug_process_input(program: &[i64], inputs: &[i64]) -> (Vec<IntcodeInstruction>, Vec<i64>) {
     let mut machine = IntcodeMachine::new_automated_machine(program, inputs);
     let instructions = machine.debug();
     let (_, _, _, output_handler) = machine.teardown();
-    (instructions, output_handler.history().to_vec())
+    (instructions, output_handler.into_sink())
+}
+
+// Feeds each machine's outputs to the other until both halt or both wait on input
+// that will never come. Returns the last value b sent to a.
+pub fn couple<I, O>(a: &mut IntcodeMachine<I, O>, b: &mut IntcodeMachine<I, O>) -> Option<i64>
+where I: IntcodeInput,
+      O: IntcodeOutput,
+{
+    let mut to_a = VecDeque::new();
+    let mut to_b = VecDeque::new();
+    let mut last_received = None;
+
+    loop {
+        let a_progressed = service(a, &mut to_a, &mut to_b);
+        let b_progressed = service(b, &mut to_b, &mut to_a);
+
+        if let Some(&value) = to_a.back() {
+            last_received = Some(value);
+        }
+
+        if !a_progressed && !b_progressed {
+            return last_received;
+        }
+    }
+}
+
+fn service<I, O>(machine: &mut IntcodeMachine<I, O>, inbox: &mut VecDeque<i64>, outbox: &mut VecDeque<i64>) -> bool
+where I: IntcodeInput,
+      O: IntcodeOutput,
+{
+    match machine.step_until_event() {
+        IntcodeEvent::Produced(value) => {
+            outbox.push_back(value);
+            true
+        },
+        IntcodeEvent::AwaitingInput => match inbox.pop_front() {
+            Some(value) => {
+                machine.input(value);
+                true
+            },
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+// A trace that ends early diverges where the shorter one stops
+pub fn diff_traces(a: &[(usize, IntcodeInstruction)], b: &[(usize, IntcodeInstruction)]) -> Option<usize> {
+    match a.iter().zip(b).position(|(x, y)| x != y) {
+        Some(index) => Some(index),
+        None if a.len() != b.len() => Some(a.len().min(b.len())),
+        None => None,
+    }
+}
+
+pub fn io_table(program: &[i64], inputs: &[i64]) -> Vec<(i64, Vec<i64>)> {
+    inputs.iter()
+        .map(|&input| {
+            let mut machine = IntcodeMachine::new(program, IntcodePresetInput::new(&[input]), IntcodeSinkOutput::new(Vec::new()));
+            machine.run();
+            let (_, _, _, output_handler) = machine.teardown();
+            (input, output_handler.into_sink())
+        })
+        .collect()
+}
+
+pub fn run_to_outputs(program: &[i64], inputs: &[i64]) -> Result<Vec<i64>> {
+    let mut machine = IntcodeMachine::new(program, IntcodeBlockingInput, IntcodeSinkOutput::new(Vec::new()));
+    let mut inputs = inputs.iter();
+
+    machine.try_run()?;
+    while machine.state() == &IntcodeState::Suspended {
+        let input = inputs.next().ok_or_else(|| anyhow!("Ran out of inputs"))?;
+        machine.input(*input);
+        machine.try_run()?;
+    }
+    machine.ensure_halted()?;
+
+    let (_, _, _, output_handler) = machine.teardown();
+    Ok(output_handler.into_sink())
+}
+
+// Replays every recorded input in order, so a session that needs more input
+// or leaves some unused has diverged from the recording
+pub fn replay_session(program: &[i64], recorded_inputs: &[i64]) -> Result<Vec<i64>> {
+    let mut machine = IntcodeMachine::new(program, IntcodePresetInput::new(recorded_inputs), IntcodeSinkOutput::new(Vec::new()))
+        .with_strict_input(true);
+
+    machine.try_run()?;
+    machine.ensure_halted()?;
+    if machine.input_handler().has_input() {
+        bail!("Session halted before consuming all {} recorded inputs", recorded_inputs.len());
+    }
+
+    let (_, _, _, output_handler) = machine.teardown();
+    Ok(output_handler.into_sink())
+}
+
+// Programs without a matching entry in inputs are run with no inputs
+pub fn run_all(programs: &[Vec<i64>], inputs: &[Vec<i64>]) -> Vec<Result<Vec<i64>>> {
+    programs.iter()
+        .enumerate()
+        .map(|(i, program)| run_to_outputs(program, inputs.get(i).map_or(&[], |inputs| &inputs[..])))
+        .collect()
+}
+
+// Talks to an ASCII program over stdin and stdout until it halts or stdin closes
+pub fn repl(program: &[i64]) -> Result<()> {
+    IntcodeMachine::new_with_inputs(program, Vec::new()).run_interactive()
+}
+
+// Instructions the worker runs between checks for the timeout
+const TIMEOUT_SLICE: u64 = 10_000;
+
+// Runs the machine on a worker thread in slices of run_for, so breakpoints and
+// failures behave just as they do for run(). A machine that times out is left
+// Running wherever its last slice ended, and running it again carries on.
+pub fn run_with_timeout<I, O>(machine: &mut IntcodeMachine<I, O>, timeout: Duration) -> Result<IntcodeState>
+where I: IntcodeInput + Send,
+      O: IntcodeOutput + Send,
+{
+    let cancelled = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    std::thread::scope(|scope| {
+        let cancelled = &cancelled;
+        let worker = scope.spawn(move || {
+            let result = loop {
+                match machine.run_for(TIMEOUT_SLICE) {
+                    Ok(IntcodeState::Running) if !cancelled.load(Ordering::Relaxed) => continue,
+                    result => break result,
+                }
+            };
+            let _ = sender.send(());
+            result
+        });
+
+        if receiver.recv_timeout(timeout).is_err() {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+
+        match worker.join().map_err(|_| anyhow!("Machine thread panicked"))?? {
+            IntcodeState::Running => Err(anyhow!("Machine didn't stop within {:?}", timeout)),
+            state => Ok(state),
+        }
+    })
+}
+
+// Cells past the end of the shorter slice are treated as zero, so a program can
+// be diffed directly against the machine's larger memory.
+pub fn memory_diff(before: &[i64], after: &[i64]) -> Vec<(usize, i64, i64)> {
+    (0..before.len().max(after.len()))
+        .map(|address| (address, *before.get(address).unwrap_or(&0), *after.get(address).unwrap_or(&0)))
+        .filter(|(_, old, new)| old != new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_diff() {
+        assert_eq!(memory_diff(&[1, 2, 3], &[1, 2, 3]), vec![]);
+        assert_eq!(memory_diff(&[1, 2, 3], &[1, 5, 3, 0, 7]), vec![(1, 2, 5), (4, 0, 7)]);
+        assert_eq!(memory_diff(&[1, 2, 3], &[1]), vec![(1, 2, 0), (2, 3, 0)]);
+    }
+
+    fn trace(program: &[i64], inputs: &[i64]) -> Vec<(usize, IntcodeInstruction)> {
+        IntcodeMachine::new_automated_machine(program, inputs).trace()
+    }
+
+    #[test]
+    fn test_couple() {
+        // a adds one to everything forever, b doubles until it passes 100
+        let mut a = IntcodeMachine::new_automated_machine(&[3,20,1001,20,1,20,4,20,1105,1,0], &[0]);
+        let mut b = IntcodeMachine::new_automated_machine(&[3,30,1002,30,2,30,4,30,1007,30,100,31,1005,31,0,99], &[]);
+
+        assert_eq!(couple(&mut a, &mut b), Some(126));
+        assert_eq!(b.state(), &IntcodeState::Halted);
+        assert_eq!(a.output_handler().buffered().last(), Some(&127));
+    }
+
+    #[test]
+    fn test_diff_traces() {
+        let equals_8 = [3,15,8,15,16,15,1005,15,12,104,0,99,104,1,99,-1,8];
+        let optimized = [3,15,1008,15,8,15,1005,15,12,104,0,99,104,1,99,-1,8];
+
+        assert_eq!(diff_traces(&trace(&equals_8, &[8]), &trace(&equals_8, &[8])), None);
+        assert_eq!(diff_traces(&trace(&equals_8, &[8]), &trace(&equals_8, &[7])), Some(3));
+        assert_eq!(diff_traces(&trace(&equals_8, &[8]), &trace(&optimized, &[8])), Some(1));
+
+        let full = trace(&equals_8, &[8]);
+        assert_eq!(diff_traces(&full, &full[..2]), Some(2));
+    }
+
+    #[test]
+    fn test_io_table() {
+        let equals_8 = [3,9,8,9,10,9,4,9,99,-1,8];
+        assert_eq!(io_table(&equals_8, &[7, 8, 9]), vec![(7, vec![0]), (8, vec![1]), (9, vec![0])]);
+
+        let less_than_8 = [3,3,1107,-1,8,3,4,3,99];
+        assert_eq!(io_table(&less_than_8, &[-1, 8]), vec![(-1, vec![1]), (8, vec![0])]);
+    }
+
+    #[test]
+    fn test_run_to_outputs() {
+        assert_eq!(run_to_outputs(&[3,0,4,0,104,7,99], &[42]).unwrap(), vec![42, 7]);
+        assert!(run_to_outputs(&[3,0,3,0,99], &[1]).is_err());
+    }
+
+    #[test]
+    fn test_replay_session() {
+        // Doubles each input until it reads a 0
+        let program = [3,100,1006,100,14,1002,100,2,101,4,101,1105,1,0,99];
+
+        let mut session = IntcodeMachine::new_blocking_machine(&program);
+        session.enable_input_log();
+        session.run();
+        for input in &[3, 5, 0] {
+            session.input(*input);
+            session.run();
+        }
+        assert_eq!(session.state(), &IntcodeState::Halted);
+
+        let outputs = session.output_handler().buffered().to_vec();
+        assert_eq!(replay_session(&program, session.consumed_inputs()).unwrap(), outputs);
+
+        let recorded = session.consumed_inputs();
+        assert!(replay_session(&program, &recorded[..recorded.len() - 1]).is_err());
+        assert!(replay_session(&program, &[recorded, &[1]].concat()).is_err());
+    }
+
+    #[test]
+    fn test_run_all() {
+        let programs = vec![
+            vec![3,9,8,9,10,9,4,9,99,-1,8],
+            vec![3,9,8,9,10,9,4,9,99,-1,8],
+            vec![104,1125899906842624,99],
+            vec![3,0,99],
+        ];
+        let inputs = vec![vec![8], vec![7], vec![]];
+
+        let results = run_all(&programs, &inputs);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap(), &vec![1]);
+        assert_eq!(results[1].as_ref().unwrap(), &vec![0]);
+        assert_eq!(results[2].as_ref().unwrap(), &vec![1125899906842624]);
+        assert!(results[3].is_err());
+    }
+
+    #[test]
+    fn test_run_with_timeout() {
+        let mut machine = IntcodeMachine::new_automated_machine(&[3,0,4,0,99], &[42]);
+        assert_eq!(run_with_timeout(&mut machine, Duration::from_secs(5)).unwrap(), IntcodeState::Halted);
+        assert_eq!(machine.output_handler().buffered(), &[42]);
+
+        let mut spinning = IntcodeMachine::new_automated_machine(&[1105,1,0], &[]);
+        assert!(run_with_timeout(&mut spinning, Duration::from_millis(50)).is_err());
+        assert_eq!(spinning.state(), &IntcodeState::Running);
+        assert_eq!(spinning.program_counter(), 0);
+
+        let mut machine = IntcodeMachine::new_automated_machine(&[1101,1,1,5,99,0], &[]);
+        machine.add_breakpoint(4);
+        assert_eq!(run_with_timeout(&mut machine, Duration::from_secs(5)).unwrap(), IntcodeState::Breakpoint(4));
+    }
+
+    #[test]
+    fn test_memory_diff_after_run() {
+        let program = [1,9,10,3,2,3,11,0,99,30,40,50];
+        let mut machine = IntcodeMachine::new_automated_machine(&program, &[]);
+        machine.run();
+
+        assert_eq!(memory_diff(&program, machine.memory()), vec![(0, 1, 3500), (3, 3, 70)]);
+    }
 }
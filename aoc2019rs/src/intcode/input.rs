@@ -1,40 +1,156 @@
+use std::collections::VecDeque;
 use std::str::FromStr;
 
 use crate::utils::input;
 
 pub trait IntcodeInput {
     fn process(&mut self) -> Option<i64>;
+    fn has_input(&self) -> bool;
+
+    // Drops anything still waiting to be read
+    fn clear(&mut self) {}
+}
+
+// Handlers whose unread inputs can be saved with a machine and handed back.
+// from_queued is None when the handler has no way to hold them.
+pub trait IntcodeQueuedInput: IntcodeInput + Sized {
+    fn queued(&self) -> Vec<i64>;
+    fn from_queued(queued: Vec<i64>) -> Option<Self>;
+}
+
+// What an Input instruction does when the handler says nothing is available.
+// Block still asks the handler, which may wait or come back empty.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntcodeInputPolicy {
+    Block,
+    Pause,
+    Error,
+    Default(i64),
+}
+
+impl IntcodeInputPolicy {
+    pub fn waits(&self) -> bool {
+        matches!(self, IntcodeInputPolicy::Block | IntcodeInputPolicy::Pause)
+    }
 }
 
+#[derive(Clone)]
 pub struct IntcodeConsoleInput;
 
 impl IntcodeInput for IntcodeConsoleInput {
+    // Asks again until it gets a number, and gives up when the console closes
     fn process(&mut self) -> Option<i64> {
-        let input = input::read_input().expect("Error reading input");
-        Some(i64::from_str(&input).expect("Error parsing input"))
+        loop {
+            let input = input::read_input().ok()?;
+            match i64::from_str(input.trim()) {
+                Ok(value) => return Some(value),
+                Err(_) => eprintln!("Not a number: {}", input),
+            }
+        }
+    }
+
+    fn has_input(&self) -> bool {
+        true
     }
 }
 
+#[derive(Clone)]
 pub struct IntcodePresetInput {
-    inputs: Box<dyn Iterator<Item=i64>>,
+    inputs: std::vec::IntoIter<i64>,
 }
 
 impl IntcodePresetInput {
     pub fn new(inputs: &[i64]) -> Self {
-        Self { inputs: Box::new(inputs.to_vec().into_iter()) }
+        Self { inputs: inputs.to_vec().into_iter() }
     }
 }
 
 impl IntcodeInput for IntcodePresetInput {
     fn process(&mut self) -> Option<i64> {
-        Some(self.inputs.next().expect("Ran out of inputs"))
+        self.inputs.next()
+    }
+
+    fn has_input(&self) -> bool {
+        !self.inputs.as_slice().is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.inputs = Vec::new().into_iter();
     }
 }
 
+impl IntcodeQueuedInput for IntcodePresetInput {
+    fn queued(&self) -> Vec<i64> {
+        self.inputs.as_slice().to_vec()
+    }
+
+    fn from_queued(queued: Vec<i64>) -> Option<Self> {
+        Some(Self::new(&queued))
+    }
+}
+
+#[derive(Clone)]
 pub struct IntcodeBlockingInput;
 
 impl IntcodeInput for IntcodeBlockingInput {
     fn process(&mut self) -> Option<i64> {
         None
     }
+
+    fn has_input(&self) -> bool {
+        false
+    }
+}
+
+impl IntcodeQueuedInput for IntcodeBlockingInput {
+    fn queued(&self) -> Vec<i64> {
+        Vec::new()
+    }
+
+    fn from_queued(queued: Vec<i64>) -> Option<Self> {
+        match queued.is_empty() {
+            true => Some(IntcodeBlockingInput),
+            false => None,
+        }
+    }
+}
+
+// Runs dry without panicking, so an empty queue suspends the machine until more is pushed
+#[derive(Clone)]
+pub struct IntcodeQueueInput {
+    queue: VecDeque<i64>,
+}
+
+impl IntcodeQueueInput {
+    pub fn new(inputs: Vec<i64>) -> Self {
+        Self { queue: inputs.into() }
+    }
+
+    pub fn push(&mut self, value: i64) {
+        self.queue.push_back(value);
+    }
+}
+
+impl IntcodeInput for IntcodeQueueInput {
+    fn process(&mut self) -> Option<i64> {
+        self.queue.pop_front()
+    }
+
+    fn has_input(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
+impl IntcodeQueuedInput for IntcodeQueueInput {
+    fn queued(&self) -> Vec<i64> {
+        self.queue.iter().copied().collect()
+    }
+
+    fn from_queued(queued: Vec<i64>) -> Option<Self> {
+        Some(Self::new(queued))
+    }
 }
@@ -1,92 +1,247 @@
 use crate::utils::conversion;
-use crate::intcode::IntcodeValue;
+use crate::intcode::{IntcodeValue, IntcodeError};
+
+#[derive(Debug, PartialEq)]
+pub enum IntcodeAdvance {
+    Step(usize),
+    // Signed, since a jump can target a negative address and the machine has to reject it
+    Jump(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntcodeCategory {
+    Arithmetic,
+    IO,
+    ControlFlow,
+    RelativeBase,
+    Halt,
+}
 
 #[derive(Clone, PartialEq)]
 pub enum IntcodeInstruction {
-    Add{x: IntcodeValue, y: IntcodeValue, position: usize},
-    Multiply{x: IntcodeValue, y: IntcodeValue, position: usize},
-    Input{position: usize},
+    Add{x: IntcodeValue, y: IntcodeValue, position: IntcodeValue},
+    Multiply{x: IntcodeValue, y: IntcodeValue, position: IntcodeValue},
+    Input{position: IntcodeValue},
     Output{value: IntcodeValue},
     JumpIfTrue{test_position: IntcodeValue, jump_position: IntcodeValue},
     JumpIfFalse{test_position: IntcodeValue, jump_position: IntcodeValue},
-    IsLessThan{x: IntcodeValue, y: IntcodeValue, position: usize},
-    IsEquals{x: IntcodeValue, y: IntcodeValue, position: usize},
+    IsLessThan{x: IntcodeValue, y: IntcodeValue, position: IntcodeValue},
+    IsEquals{x: IntcodeValue, y: IntcodeValue, position: IntcodeValue},
     SetRelativeBase{offset: IntcodeValue},
     Halt,
 }
 
 impl IntcodeInstruction {
     pub fn new(opcode_and_param_modes: i64, params: &[i64]) -> Self {
+        Self::try_new(opcode_and_param_modes, params).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    // Errors report address 0, since there's no memory around the instruction
+    pub fn try_new(opcode_and_param_modes: i64, params: &[i64]) -> Result<Self, IntcodeError> {
+        let mut word = [opcode_and_param_modes, 0, 0, 0];
+        let count = params.len().min(3);
+        word[1..=count].copy_from_slice(&params[..count]);
+        Self::decode(&word[..=count], 0)
+    }
+
+    // Parameters past the end of memory read as 0
+    pub fn decode(memory: &[i64], address: usize) -> Result<Self, IntcodeError> {
         use IntcodeInstruction::*;
 
-        let digits: Vec<usize> = conversion::i64_into_digits(&opcode_and_param_modes)
-            .into_iter()
-            .rev()
-            .collect();
-            
-        let opcode = digits[0] + 10 * digits.get(1).unwrap_or(&0);
-        let get_value = |param_position| {
-            let mode = *digits.get(param_position + 2).unwrap_or(&0);
+        let opcode_and_param_modes = *memory.get(address).ok_or(IntcodeError::PcOutOfBounds(address))?;
+        if opcode_and_param_modes < 0 {
+            return Err(IntcodeError::InvalidOpcode(opcode_and_param_modes, address));
+        }
+        let mut params = [0; 3];
+        for (param, value) in params.iter_mut().zip(memory.iter().skip(address + 1)) {
+            *param = *value;
+        }
+
+        let opcode = opcode_and_param_modes % 100;
+        let modes = conversion::parameter_modes(opcode_and_param_modes);
+        let get_value = |param_position: usize| {
+            let mode = modes[param_position] as usize;
             match mode {
-                0 => IntcodeValue::Position(params[param_position] as usize),
-                1 => IntcodeValue::Immediate(params[param_position]),
-                2 => IntcodeValue::Relative(params[param_position]),
-                _ => panic!("Invalid parameter mode: {}", mode),
+                0 if params[param_position] < 0 => Err(IntcodeError::InvalidAddress(params[param_position], address)),
+                0 => Ok(IntcodeValue::Position(params[param_position] as usize)),
+                1 => Ok(IntcodeValue::Immediate(params[param_position])),
+                2 => Ok(IntcodeValue::Relative(params[param_position])),
+                _ => Err(IntcodeError::InvalidParameterMode(mode, address)),
             }
         };
 
-        match opcode {
+        let instruction = match opcode {
             1 => {
                 Add {
-                    x: get_value(0),
-                    y: get_value(1),
-                    position: params[2] as usize,
+                    x: get_value(0)?,
+                    y: get_value(1)?,
+                    position: get_value(2)?,
                 }
             },
             2 =>  {
                 Multiply{ 
-                    x: get_value(0),
-                    y: get_value(1),
-                    position: params[2] as usize,
+                    x: get_value(0)?,
+                    y: get_value(1)?,
+                    position: get_value(2)?,
                 }                
             },
             3 =>  {
-                Input{ position: params[0] as usize }
+                Input{ position: get_value(0)? }
             },
             4 =>  {
                 Output{ 
-                    value: get_value(0)
+                    value: get_value(0)?
                 }
             },
             5 => {
                 JumpIfTrue { 
-                    test_position: get_value(0),
-                    jump_position: get_value(1),
+                    test_position: get_value(0)?,
+                    jump_position: get_value(1)?,
                 }
             },
             6 => {
                 JumpIfFalse { 
-                    test_position: get_value(0),
-                    jump_position: get_value(1),
+                    test_position: get_value(0)?,
+                    jump_position: get_value(1)?,
                 }
             },
             7 => {
                 IsLessThan {
-                    x: get_value(0),
-                    y: get_value(1),
-                    position: params[2] as usize,
+                    x: get_value(0)?,
+                    y: get_value(1)?,
+                    position: get_value(2)?,
                 }
             },
             8 => {
                 IsEquals {
-                    x: get_value(0),
-                    y: get_value(1),
-                    position: params[2] as usize,
+                    x: get_value(0)?,
+                    y: get_value(1)?,
+                    position: get_value(2)?,
                 }
             },
-            9 => SetRelativeBase { offset: get_value(0) },
+            9 => SetRelativeBase { offset: get_value(0)? },
             99 => Halt,
-            _ => panic!("Invalid instruction: {:?}", opcode),
+            _ => return Err(IntcodeError::InvalidOpcode(opcode_and_param_modes, address)),
+        };
+
+        Ok(instruction)
+    }
+
+    pub fn length(&self) -> usize {
+        use IntcodeInstruction::*;
+
+        match self {
+            Add{..} | Multiply{..} | IsLessThan{..} | IsEquals{..} => 4,
+            JumpIfTrue{..} | JumpIfFalse{..} => 3,
+            Input{..} | Output{..} | SetRelativeBase{..} => 2,
+            Halt => 1,
+        }
+    }
+
+    // The parameter this instruction writes its result to, if any
+    pub fn write_target(&self) -> Option<&IntcodeValue> {
+        use IntcodeInstruction::*;
+
+        match self {
+            Add{position, ..} | Multiply{position, ..} | IsLessThan{position, ..} | IsEquals{position, ..} | Input{position} => Some(position),
+            _ => None,
+        }
+    }
+
+    // Every parameter whose value is read, including jump targets
+    pub fn read_operands(&self) -> Vec<&IntcodeValue> {
+        use IntcodeInstruction::*;
+
+        match self {
+            Add{x, y, ..} | Multiply{x, y, ..} | IsLessThan{x, y, ..} | IsEquals{x, y, ..} => vec![x, y],
+            JumpIfTrue{test_position, jump_position} | JumpIfFalse{test_position, jump_position} => vec![test_position, jump_position],
+            Output{value} => vec![value],
+            SetRelativeBase{offset} => vec![offset],
+            Input{..} | Halt => vec![],
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        use IntcodeInstruction::*;
+
+        match self {
+            Add{..} => "Add",
+            Multiply{..} => "Mul",
+            Input{..} => "Inp",
+            Output{..} => "Out",
+            JumpIfTrue{..} => "JmT",
+            JumpIfFalse{..} => "JmF",
+            IsLessThan{..} => "Lst",
+            IsEquals{..} => "Eqt",
+            SetRelativeBase{..} => "Srb",
+            Halt => "Halt",
+        }
+    }
+
+    // Comparisons count as arithmetic since they just write a 0 or 1
+    pub fn category(&self) -> IntcodeCategory {
+        use IntcodeInstruction::*;
+
+        match self {
+            Add{..} | Multiply{..} | IsLessThan{..} | IsEquals{..} => IntcodeCategory::Arithmetic,
+            Input{..} | Output{..} => IntcodeCategory::IO,
+            JumpIfTrue{..} | JumpIfFalse{..} => IntcodeCategory::ControlFlow,
+            SetRelativeBase{..} => IntcodeCategory::RelativeBase,
+            Halt => IntcodeCategory::Halt,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<i64> {
+        use IntcodeInstruction::*;
+
+        let (opcode, params) = match self {
+            Add{x, y, position} => (1, vec![x.encode(), y.encode(), position.encode()]),
+            Multiply{x, y, position} => (2, vec![x.encode(), y.encode(), position.encode()]),
+            Input{position} => (3, vec![position.encode()]),
+            Output{value} => (4, vec![value.encode()]),
+            JumpIfTrue{test_position, jump_position} => (5, vec![test_position.encode(), jump_position.encode()]),
+            JumpIfFalse{test_position, jump_position} => (6, vec![test_position.encode(), jump_position.encode()]),
+            IsLessThan{x, y, position} => (7, vec![x.encode(), y.encode(), position.encode()]),
+            IsEquals{x, y, position} => (8, vec![x.encode(), y.encode(), position.encode()]),
+            SetRelativeBase{offset} => (9, vec![offset.encode()]),
+            Halt => (99, vec![]),
+        };
+
+        let modes: i64 = params.iter()
+            .zip([100, 1000, 10000].iter())
+            .map(|((mode, _), place)| mode * place)
+            .sum();
+
+        std::iter::once(opcode + modes)
+            .chain(params.into_iter().map(|(_, word)| word))
+            .collect()
+    }
+
+    // Err carries the operand that points below address 0
+    pub fn advance(&self, memory: &[i64], relative_base: i64) -> Result<IntcodeAdvance, &IntcodeValue> {
+        use IntcodeInstruction::*;
+
+        let jump = match self {
+            JumpIfTrue{test_position, jump_position} => {
+                if test_position.evaluate(memory, relative_base).ok_or(test_position)? != 0 {
+                    Some(jump_position)
+                } else {
+                    None
+                }
+            },
+            JumpIfFalse{test_position, jump_position} => {
+                if test_position.evaluate(memory, relative_base).ok_or(test_position)? == 0 {
+                    Some(jump_position)
+                } else {
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        match jump {
+            Some(target) => Ok(IntcodeAdvance::Jump(target.evaluate(memory, relative_base).ok_or(target)?)),
+            None => Ok(IntcodeAdvance::Step(self.length())),
         }
     }
 }
@@ -112,6 +267,25 @@ impl std::fmt::Debug for IntcodeInstruction {
     }
 }
 
+impl std::fmt::Display for IntcodeInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use IntcodeInstruction::*;
+
+        match self {
+            Add{x, y, position} => write!(f, "Add {} {} {}", x, y, position),
+            Multiply{x, y, position} => write!(f, "Mul {} {} {}", x, y, position),
+            Input{position} => write!(f, "Inp {}", position),
+            Output{value} => write!(f, "Out {}", value),
+            JumpIfTrue{test_position, jump_position} => write!(f, "JmT {} {}", test_position, jump_position),
+            JumpIfFalse{test_position, jump_position} => write!(f, "JmF {} {}", test_position, jump_position),
+            IsLessThan{x, y, position} => write!(f, "Lst {} {} {}", x, y, position),
+            IsEquals{x, y, position} => write!(f, "Eqt {} {} {}", x, y, position),
+            SetRelativeBase{offset} => write!(f, "Srb {}", offset),
+            Halt => write!(f, "Halt"),
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -123,36 +297,36 @@ mod tests {
     fn test_param_modes() {
         assert_eq!(
             IntcodeInstruction::new(1, &[1, 2, 3]), 
-            Add{x: Position(1), y: Position(2), position: 3});
+            Add{x: Position(1), y: Position(2), position: Position(3)});
 
         assert_eq!(
             IntcodeInstruction::new(101, &[4, 5, 6]), 
-            Add{x: Immediate(4), y: Position(5), position: 6});
+            Add{x: Immediate(4), y: Position(5), position: Position(6)});
 
         assert_eq!(
             IntcodeInstruction::new(1001, &[4, 5, 6]), 
-            Add{x: Position(4), y: Immediate(5), position: 6});
+            Add{x: Position(4), y: Immediate(5), position: Position(6)});
 
         assert_eq!(
             IntcodeInstruction::new(1101, &[4, 5, 6]), 
-            Add{x: Immediate(4), y: Immediate(5), position: 6});
+            Add{x: Immediate(4), y: Immediate(5), position: Position(6)});
     }
 
     #[test]
     fn test_add() {
         assert_eq!(
             IntcodeInstruction::new(1, &[0, 1, 2]), 
-            Add{x: Position(0), y: Position(1), position: 2}
+            Add{x: Position(0), y: Position(1), position: Position(2)}
         );
             
         assert_eq!(
             IntcodeInstruction::new(101, &[0, 1, 2]), 
-            Add{x: Immediate(0), y: Position(1), position: 2}
+            Add{x: Immediate(0), y: Position(1), position: Position(2)}
         );
 
         assert_eq!(
             IntcodeInstruction::new(1001, &[0, 1, 2]), 
-            Add{x: Position(0), y: Immediate(1), position: 2}
+            Add{x: Position(0), y: Immediate(1), position: Position(2)}
         );
     }
 
@@ -160,17 +334,17 @@ mod tests {
     fn test_multiply() {
         assert_eq!(
             IntcodeInstruction::new(2, &[0, 1, 2]), 
-            Multiply{x: Position(0), y: Position(1), position: 2}
+            Multiply{x: Position(0), y: Position(1), position: Position(2)}
         );
 
         assert_eq!(
             IntcodeInstruction::new(102, &[0, 1, 2]), 
-            Multiply{x: Immediate(0), y: Position(1), position: 2}
+            Multiply{x: Immediate(0), y: Position(1), position: Position(2)}
         );
 
         assert_eq!(
             IntcodeInstruction::new(1002, &[0, 1, 2]), 
-            Multiply{x: Position(0), y: Immediate(1), position: 2}
+            Multiply{x: Position(0), y: Immediate(1), position: Position(2)}
         );
     }
 
@@ -178,7 +352,7 @@ mod tests {
     fn test_input() {
         assert_eq!(
             IntcodeInstruction::new(3, &[0]), 
-            Input{position: 0}
+            Input{position: Position(0)}
         );
     }
     
@@ -235,17 +409,17 @@ mod tests {
     fn test_less_than() {
         assert_eq!(
             IntcodeInstruction::new(7, &[0, 1, 2]), 
-            IsLessThan{x: Position(0), y: Position(1), position: 2}
+            IsLessThan{x: Position(0), y: Position(1), position: Position(2)}
         );
 
         assert_eq!(
             IntcodeInstruction::new(107, &[0, 1, 2]), 
-            IsLessThan{x: Immediate(0), y: Position(1), position: 2}
+            IsLessThan{x: Immediate(0), y: Position(1), position: Position(2)}
         );
 
         assert_eq!(
             IntcodeInstruction::new(1007, &[0, 1, 2]), 
-            IsLessThan{x: Position(0), y: Immediate(1), position: 2}
+            IsLessThan{x: Position(0), y: Immediate(1), position: Position(2)}
         );
     }
     
@@ -253,17 +427,17 @@ mod tests {
     fn test_equals() {
         assert_eq!(
             IntcodeInstruction::new(8, &[0, 1, 2]), 
-            IsEquals{x: Position(0), y: Position(1), position: 2}
+            IsEquals{x: Position(0), y: Position(1), position: Position(2)}
         );
 
         assert_eq!(
             IntcodeInstruction::new(108, &[0, 1, 2]), 
-            IsEquals{x: Immediate(0), y: Position(1), position: 2}
+            IsEquals{x: Immediate(0), y: Position(1), position: Position(2)}
         );
 
         assert_eq!(
             IntcodeInstruction::new(1008, &[0, 1, 2]), 
-            IsEquals{x: Position(0), y: Immediate(1), position: 2}
+            IsEquals{x: Position(0), y: Immediate(1), position: Position(2)}
         );
     }
 
@@ -289,4 +463,104 @@ mod tests {
     fn test_halt() {
         assert_eq!(IntcodeInstruction::new(99, &[]), Halt); 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_invalid_parameter_mode() {
+        assert_eq!(IntcodeInstruction::try_new(301, &[0]), Err(IntcodeError::InvalidParameterMode(3, 0)));
+        assert_eq!(IntcodeInstruction::try_new(204, &[3]), Ok(Output{value: Relative(3)}));
+        assert_eq!(IntcodeInstruction::decode(&[301, 0], 0), Err(IntcodeError::InvalidParameterMode(3, 0)));
+        assert_eq!(IntcodeInstruction::decode(&[99, 10301, 0, 1, 2], 1), Err(IntcodeError::InvalidParameterMode(3, 1)));
+        assert_eq!(IntcodeInstruction::decode(&[1901, 0, 1, 2], 0), Err(IntcodeError::InvalidParameterMode(9, 0)));
+        assert_eq!(IntcodeInstruction::decode(&[204, 3], 0), Ok(Output{value: Relative(3)}));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(IntcodeInstruction::decode(&[-1001, 0, 0, 0], 0), Err(IntcodeError::InvalidOpcode(-1001, 0)));
+        assert_eq!(IntcodeInstruction::decode(&[1, 0, 0, 0, 42], 4), Err(IntcodeError::InvalidOpcode(42, 4)));
+        assert_eq!(IntcodeInstruction::decode(&[1, 0, 0, 0], 4), Err(IntcodeError::PcOutOfBounds(4)));
+        assert_eq!(IntcodeInstruction::decode(&[1, 5], 0), Ok(Add{x: Position(5), y: Position(0), position: Position(0)}));
+    }
+
+    #[test]
+    fn test_length() {
+        let lengths: Vec<usize> = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99].iter()
+            .map(|&opcode| IntcodeInstruction::new(opcode, &[0, 0, 0]).length())
+            .collect();
+        assert_eq!(lengths, vec![4, 4, 2, 2, 3, 3, 4, 4, 2, 1]);
+
+        for opcode in &[1, 2, 3, 4, 5, 6, 7, 8, 9, 99] {
+            let instruction = IntcodeInstruction::new(*opcode, &[0, 0, 0]);
+            assert_eq!(instruction.encode().len(), instruction.length());
+        }
+    }
+
+    #[test]
+    fn test_write_target() {
+        assert_eq!(IntcodeInstruction::new(1, &[1, 2, 3]).write_target(), Some(&Position(3)));
+        assert_eq!(IntcodeInstruction::new(10001, &[1, 2, 3]).write_target(), Some(&Immediate(3)));
+        assert_eq!(IntcodeInstruction::new(203, &[-1]).write_target(), Some(&Relative(-1)));
+        assert_eq!(IntcodeInstruction::new(104, &[1]).write_target(), None);
+        assert_eq!(IntcodeInstruction::new(1105, &[1, 9]).write_target(), None);
+    }
+
+    #[test]
+    fn test_read_operands() {
+        assert_eq!(IntcodeInstruction::new(1001, &[1, 2, 3]).read_operands(), vec![&Position(1), &Immediate(2)]);
+        assert_eq!(IntcodeInstruction::new(1205, &[-1, 9]).read_operands(), vec![&Relative(-1), &Immediate(9)]);
+        assert_eq!(IntcodeInstruction::new(209, &[4]).read_operands(), vec![&Relative(4)]);
+        assert!(IntcodeInstruction::new(3, &[0]).read_operands().is_empty());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", IntcodeInstruction::new(1, &[1, 2, 3])), "Add [1] [2] [3]");
+        assert_eq!(format!("{}", IntcodeInstruction::new(1102, &[4, -5, 6])), "Mul 4 -5 [6]");
+        assert_eq!(format!("{}", IntcodeInstruction::new(204, &[-1])), "Out @-1");
+        assert_eq!(format!("{}", IntcodeInstruction::new(1005, &[7, 9])), "JmT [7] 9");
+        assert_eq!(format!("{}", IntcodeInstruction::new(99, &[])), "Halt");
+
+        assert_eq!(format!("{:?}", IntcodeInstruction::new(1, &[1, 2, 3])), "Add 1p 2p 3p");
+    }
+
+    #[test]
+    fn test_advance() {
+        let memory = [0, 1, 7];
+
+        assert_eq!(IntcodeInstruction::new(1, &[0, 1, 2]).advance(&memory, 0), Ok(IntcodeAdvance::Step(4)));
+        assert_eq!(IntcodeInstruction::new(104, &[1]).advance(&memory, 0), Ok(IntcodeAdvance::Step(2)));
+
+        assert_eq!(IntcodeInstruction::new(1105, &[1, 9]).advance(&memory, 0), Ok(IntcodeAdvance::Jump(9)));
+        assert_eq!(IntcodeInstruction::new(1105, &[0, 9]).advance(&memory, 0), Ok(IntcodeAdvance::Step(3)));
+        assert_eq!(IntcodeInstruction::new(6, &[0, 2]).advance(&memory, 0), Ok(IntcodeAdvance::Jump(7)));
+        assert_eq!(IntcodeInstruction::new(206, &[-1, 1]).advance(&memory, 2), Ok(IntcodeAdvance::Step(3)));
+
+        // Any non-zero value counts as true, including negative ones
+        assert_eq!(IntcodeInstruction::new(1105, &[-4, 9]).advance(&memory, 0), Ok(IntcodeAdvance::Jump(9)));
+        assert_eq!(IntcodeInstruction::new(1106, &[-4, 9]).advance(&memory, 0), Ok(IntcodeAdvance::Step(3)));
+
+        assert_eq!(IntcodeInstruction::new(2205, &[-5, 0]).advance(&memory, 2), Err(&IntcodeValue::Relative(-5)));
+    }
+
+    #[test]
+    fn test_encode() {
+        for word in &[[1, 1, 2, 3], [1102, 4, -5, 6], [204, -1, 0, 0], [1108, 2, -3, 9], [1005, 7, 9, 0], [21101, 1, 2, 3], [203, -1, 0, 0], [99, 0, 0, 0]] {
+            let instruction = IntcodeInstruction::new(word[0], &word[1..]);
+            assert_eq!(instruction.encode(), &word[..instruction.length()]);
+        }
+    }
+
+    #[test]
+    fn test_relative_write_targets() {
+        assert_eq!(
+            IntcodeInstruction::new(21101, &[1, 2, -3]),
+            Add{x: Immediate(1), y: Immediate(2), position: Relative(-3)});
+
+        assert_eq!(
+            IntcodeInstruction::new(1201, &[4, 5, 6]),
+            Add{x: Relative(4), y: Immediate(5), position: Position(6)});
+
+        assert_eq!(IntcodeInstruction::new(203, &[7]), Input{position: Relative(7)});
+        assert_eq!(format!("{}", IntcodeInstruction::new(22207, &[1, 2, 3])), "Lst @1 @2 @3");
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::{anyhow, Result};
+
+use std::io::{BufReader, Read};
+use std::str::FromStr;
+
+use crate::intcode::{IntcodeMachine, IntcodeBlockingInput, IntcodeHistoryOutput};
+
+impl IntcodeMachine<IntcodeBlockingInput, IntcodeHistoryOutput> {
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let program = read_program(reader)?;
+        Ok(IntcodeMachine::new_blocking_machine(&program))
+    }
+}
+
+impl FromStr for IntcodeMachine<IntcodeBlockingInput, IntcodeHistoryOutput> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        IntcodeMachine::from_reader(s.as_bytes())
+    }
+}
+
+pub fn read_program<R: Read>(reader: R) -> Result<Vec<i64>> {
+    let mut program = Vec::new();
+    let mut token = Vec::new();
+    let mut token_start = 0;
+
+    for (offset, byte) in BufReader::new(reader).bytes().enumerate() {
+        let byte = byte?;
+        if byte == b',' {
+            program.push(parse_token(&token, token_start)?);
+            token.clear();
+            token_start = offset + 1;
+        } else {
+            token.push(byte);
+        }
+    }
+
+    // A trailing newline or comma leaves an empty final token
+    if !token.iter().all(u8::is_ascii_whitespace) {
+        program.push(parse_token(&token, token_start)?);
+    }
+
+    Ok(program)
+}
+
+fn parse_token(token: &[u8], offset: usize) -> Result<i64> {
+    let text = std::str::from_utf8(token).map_err(|_| anyhow!("Invalid UTF-8 at byte {}", offset))?;
+    i64::from_str(text.trim()).map_err(|e| anyhow!("Invalid value {:?} at byte {}: {}", text, offset, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::intcode::IntcodeState;
+
+    #[test]
+    fn test_read_program() {
+        assert_eq!(read_program("1,0,0,3,99".as_bytes()).unwrap(), vec![1, 0, 0, 3, 99]);
+        assert_eq!(read_program(" 1, -2 ,3\n".as_bytes()).unwrap(), vec![1, -2, 3]);
+        assert_eq!(read_program("1,2,\n".as_bytes()).unwrap(), vec![1, 2]);
+        assert_eq!(read_program("".as_bytes()).unwrap(), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn test_read_program_error_offset() {
+        let error = read_program("1,2,x3,4".as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("at byte 4"), "{}", error);
+
+        let error = read_program("1,,2".as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("at byte 2"), "{}", error);
+    }
+
+    #[test]
+    fn test_from_str() {
+        let mut machine: IntcodeMachine<_, _> = "1,9,10,3,2,3,11,0,99,30,40,50\n".parse().unwrap();
+        machine.run();
+        assert_eq!(machine.read_memory_position(0), 3500);
+
+        let error = IntcodeMachine::from_str("1,9, ten,3").unwrap_err();
+        assert!(error.to_string().contains("\" ten\" at byte 4"), "{}", error);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let mut machine = IntcodeMachine::from_reader(std::io::Cursor::new("1,0,0,0,99")).unwrap();
+        machine.run();
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+        assert_eq!(machine.read_memory_position(0), 2);
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{anyhow, bail, Result};
+
+use std::convert::TryFrom;
+
+use crate::intcode::{IntcodeMachine, IntcodeEvent, IntcodeState, IntcodeInput, IntcodeQueueInput, IntcodeHistoryOutput};
+
+const NAT_ADDRESS: i64 = 255;
+
+// A machine polling with -1 can take a round or two to decide to send anything,
+// so one quiet round isn't enough to call the network idle
+const IDLE_ROUNDS: usize = 2;
+
+type NetworkMachine = IntcodeMachine<IntcodeQueueInput, IntcodeHistoryOutput>;
+
+// Machines are booted with their address as the first input and then exchange
+// (address, x, y) packets, reading -1 whenever nothing has arrived for them
+pub struct IntcodeNetwork {
+    machines: Vec<NetworkMachine>,
+    partial_packets: Vec<Vec<i64>>,
+    nat: Option<(i64, i64)>,
+    idle_rounds: usize,
+}
+
+impl IntcodeNetwork {
+    pub fn new(program: &[i64], size: usize) -> Self {
+        Self {
+            machines: (0..size).map(|address| IntcodeMachine::new_with_inputs(program, vec![address as i64])).collect(),
+            partial_packets: vec![Vec::new(); size],
+            nat: None,
+            idle_rounds: 0,
+        }
+    }
+
+    pub fn nat(&self) -> Option<(i64, i64)> {
+        self.nat
+    }
+
+    // Consecutive steps where nothing was queued for anyone and nothing was sent
+    pub fn idle_rounds(&self) -> usize {
+        self.idle_rounds
+    }
+
+    // Packets left for a halted machine are never read, so they don't count
+    pub fn is_idle(&self) -> bool {
+        self.idle_rounds >= IDLE_ROUNDS
+            && self.machines.iter()
+                .filter(|machine| machine.state() != &IntcodeState::Halted)
+                .all(|machine| !machine.input_handler().has_input())
+    }
+
+    // Runs every machine that hasn't halted until it needs more input, returning
+    // false if the whole network was idle: nothing queued for anyone and nothing sent
+    pub fn step(&mut self) -> Result<bool> {
+        let mut active = false;
+
+        for index in 0..self.machines.len() {
+            let machine = &mut self.machines[index];
+            if machine.state() == &IntcodeState::Halted {
+                continue;
+            }
+
+            if machine.input_handler().has_input() {
+                active = true;
+            } else {
+                machine.push_input(-1);
+            }
+
+            if let IntcodeEvent::Failed(error) = machine.resume() {
+                bail!("Machine {} failed: {}", index, error);
+            }
+            let partial = &mut self.partial_packets[index];
+            partial.extend(machine.drain_output());
+
+            let complete = partial.len() / 3 * 3;
+            let packets: Vec<i64> = partial.drain(..complete).collect();
+            for packet in packets.chunks(3) {
+                self.send(packet[0], packet[1], packet[2])?;
+                active = true;
+            }
+        }
+
+        if active {
+            self.idle_rounds = 0;
+        } else {
+            self.idle_rounds += 1;
+        }
+        Ok(active)
+    }
+
+    pub fn run_until_nat_repeat(&mut self) -> Result<i64> {
+        let mut last_delivered = None;
+
+        loop {
+            self.step()?;
+            if !self.is_idle() {
+                continue;
+            }
+
+            let (x, y) = self.nat.ok_or_else(|| anyhow!("Network went idle before the NAT received a packet"))?;
+            if last_delivered == Some(y) {
+                return Ok(y);
+            }
+
+            self.send(0, x, y)?;
+            last_delivered = Some(y);
+        }
+    }
+
+    fn send(&mut self, address: i64, x: i64, y: i64) -> Result<()> {
+        if address == NAT_ADDRESS {
+            self.nat = Some((x, y));
+            return Ok(());
+        }
+
+        let machine = usize::try_from(address).ok()
+            .and_then(|index| self.machines.get_mut(index))
+            .ok_or_else(|| anyhow!("Packet sent to unknown address {}", address))?;
+        machine.push_inputs([x, y].iter().copied());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Boots by sending (address, address) to the NAT, then passes every packet it
+    // receives back to the NAT with y counted up until it reaches 3
+    const NAT_COUNTER: [i64; 39] = [
+        3,100,104,255,4,100,4,100,3,101,1008,101,-1,103,1005,103,8,3,102,
+        1007,102,3,103,1006,103,30,1001,102,1,102,104,255,4,101,4,102,1105,1,8,
+    ];
+
+    #[test]
+    fn test_nat_repeat() {
+        let mut network = IntcodeNetwork::new(&NAT_COUNTER, 2);
+
+        assert!(network.step().unwrap());
+        assert_eq!(network.nat(), Some((1, 1)));
+        assert!(!network.step().unwrap());
+
+        assert_eq!(network.run_until_nat_repeat().unwrap(), 3);
+        assert_eq!(network.nat(), Some((1, 3)));
+    }
+
+    #[test]
+    fn test_idle_rounds() {
+        let mut network = IntcodeNetwork::new(&NAT_COUNTER, 2);
+        network.step().unwrap();
+        assert_eq!(network.idle_rounds(), 0);
+
+        network.step().unwrap();
+        assert_eq!(network.idle_rounds(), 1);
+        assert!(!network.is_idle());
+
+        network.step().unwrap();
+        assert_eq!(network.idle_rounds(), 2);
+        assert!(network.is_idle());
+
+        network.send(0, 1, 1).unwrap();
+        assert!(!network.is_idle());
+        network.step().unwrap();
+        assert_eq!(network.idle_rounds(), 0);
+    }
+
+    #[test]
+    fn test_packets_between_machines() {
+        let mut network = IntcodeNetwork::new(&NAT_COUNTER, 3);
+        network.step().unwrap();
+        assert_eq!(network.nat(), Some((2, 2)));
+
+        network.send(1, 7, 0).unwrap();
+        assert!(network.step().unwrap());
+        assert_eq!(network.nat(), Some((7, 1)));
+        assert!(!network.step().unwrap());
+    }
+
+    #[test]
+    fn test_halted_machines() {
+        // Each machine sends one packet to the NAT and halts, so the packet the NAT
+        // delivers to machine 0 is never read
+        let mut network = IntcodeNetwork::new(&[104,255,104,5,104,9,99], 2);
+        assert_eq!(network.run_until_nat_repeat().unwrap(), 9);
+        assert!(network.is_idle());
+        assert!(!network.step().unwrap());
+    }
+
+    #[test]
+    fn test_network_errors() {
+        assert!(IntcodeNetwork::new(&[99], 2).run_until_nat_repeat().is_err());
+        assert!(IntcodeNetwork::new(&[104,2,104,1,104,1,99], 2).step().is_err());
+        assert!(IntcodeNetwork::new(&[104,-1,104,1,104,1,99], 2).step().is_err());
+        assert!(IntcodeNetwork::new(&[104,1,42], 2).step().is_err());
+
+        let mut network = IntcodeNetwork::new(&NAT_COUNTER, 2);
+        assert!(network.send(2, 0, 0).is_err());
+        assert!(network.send(-5, 0, 0).is_err());
+    }
+}
@@ -1,46 +1,196 @@
+// Handlers that keep what the machine outputs hand it back through buffered and take.
+// The rest only pass values on, so the machine has nothing to buffer or limit.
 pub trait IntcodeOutput {
     fn process(&mut self, value: i64);
-    fn history(&self) -> &[String];
-    fn last_output(&self) -> Option<&String> {
-        self.history().last()
+
+    fn buffered(&self) -> &[i64] {
+        &[]
+    }
+
+    // Removes the first count buffered values
+    fn take(&mut self, _count: usize) -> Vec<i64> {
+        Vec::new()
     }
 }
 
+// Prints each output as well as buffering it
+#[derive(Clone)]
 pub struct IntcodeConsoleOutput {
-    history: Vec<String>,
+    buffer: Vec<i64>,
 }
 
 impl IntcodeConsoleOutput {
     pub fn new() -> Self {
-        Self { history: Vec::new() }
+        Self { buffer: Vec::new() }
     }
 }
 
 impl IntcodeOutput for IntcodeConsoleOutput {
     fn process(&mut self, value: i64) {
         println!("Output: {}", value);
+        self.buffer.push(value);
+    }
+
+    fn buffered(&self) -> &[i64] {
+        &self.buffer
     }
 
-    fn history(&self) -> &[String] {
-        &self.history
+    fn take(&mut self, count: usize) -> Vec<i64> {
+        self.buffer.drain(..count).collect()
     }
 }
 
-pub struct IntcodeHistoryOutput {
-    history: Vec<String>,
+pub trait OutputSink {
+    fn send(&mut self, value: i64);
+
+    fn buffered(&self) -> &[i64] {
+        &[]
+    }
+
+    fn take(&mut self, _count: usize) -> Vec<i64> {
+        Vec::new()
+    }
 }
 
-impl IntcodeHistoryOutput {
-    pub fn new() -> Self {
-        Self { history: Vec::new() }
+impl OutputSink for Vec<i64> {
+    fn send(&mut self, value: i64) {
+        self.push(value);
+    }
+
+    fn buffered(&self) -> &[i64] {
+        self
+    }
+
+    fn take(&mut self, count: usize) -> Vec<i64> {
+        self.drain(..count).collect()
+    }
+}
+
+// A disconnected receiver means nobody is listening, so the value is dropped
+impl OutputSink for std::sync::mpsc::Sender<i64> {
+    fn send(&mut self, value: i64) {
+        let _ = std::sync::mpsc::Sender::send(self, value);
+    }
+}
+
+impl <F: FnMut(i64)> OutputSink for F {
+    fn send(&mut self, value: i64) {
+        self(value);
+    }
+}
+
+// Buffers like a Vec until a callback is set, then hands each value to it instead
+#[derive(Default)]
+pub struct CallbackSink {
+    buffer: Vec<i64>,
+    callback: Option<Box<dyn FnMut(i64)>>,
+}
+
+impl CallbackSink {
+    pub fn set_callback(&mut self, callback: Box<dyn FnMut(i64)>) {
+        self.callback = Some(callback);
+    }
+}
+
+impl OutputSink for CallbackSink {
+    fn send(&mut self, value: i64) {
+        match self.callback.as_mut() {
+            Some(callback) => callback(value),
+            None => self.buffer.push(value),
+        }
+    }
+
+    fn buffered(&self) -> &[i64] {
+        &self.buffer
+    }
+
+    fn take(&mut self, count: usize) -> Vec<i64> {
+        self.buffer.drain(..count).collect()
     }
 }
-impl IntcodeOutput for IntcodeHistoryOutput {
+
+#[derive(Clone, Default)]
+pub struct IntcodeSinkOutput<S> {
+    sink: S,
+}
+
+// The collecting handler most machines use
+pub type IntcodeHistoryOutput = IntcodeSinkOutput<Vec<i64>>;
+
+// For machines that get an output callback after they're built
+pub type IntcodeCallbackOutput = IntcodeSinkOutput<CallbackSink>;
+
+impl <S: OutputSink> IntcodeSinkOutput<S> {
+    pub fn new(sink: S) -> Self {
+        Self { sink }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+}
+
+impl <S: OutputSink> IntcodeOutput for IntcodeSinkOutput<S> {
     fn process(&mut self, value: i64) {
-        self.history.push(format!("{}", value));
+        self.sink.send(value);
+    }
+
+    fn buffered(&self) -> &[i64] {
+        self.sink.buffered()
     }
-    
-    fn history(&self) -> &[String] {
-        &self.history
+
+    fn take(&mut self, count: usize) -> Vec<i64> {
+        self.sink.take(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::intcode::{IntcodeMachine, IntcodeState, IntcodePresetInput};
+
+    const PROGRAM: [i64; 9] = [3,0,4,0,104,-7,104,12,99];
+
+    fn sink_machine<S: OutputSink>(sink: S) -> IntcodeMachine<IntcodePresetInput, IntcodeSinkOutput<S>> {
+        IntcodeMachine::new(&PROGRAM, IntcodePresetInput::new(&[5]), IntcodeSinkOutput::new(sink))
+    }
+
+    #[test]
+    fn test_vec_sink() {
+        let mut machine = sink_machine(Vec::new());
+        machine.run();
+
+        assert_eq!(machine.state(), &IntcodeState::Halted);
+        assert_eq!(machine.output_handler().sink(), &vec![5, -7, 12]);
+        assert_eq!(machine.output_handler().buffered(), &[5, -7, 12]);
+    }
+
+    #[test]
+    fn test_channel_sink() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut machine = sink_machine(sender);
+        machine.run();
+        drop(machine);
+
+        assert_eq!(receiver.iter().collect::<Vec<i64>>(), vec![5, -7, 12]);
+    }
+
+    #[test]
+    fn test_closure_sink() {
+        let mut total = 0;
+        let mut machine = sink_machine(|value| total += value);
+        machine.run();
+        drop(machine);
+
+        assert_eq!(total, 10);
     }
 }
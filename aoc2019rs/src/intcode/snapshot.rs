@@ -0,0 +1,74 @@
+use crate::intcode::{IntcodeMachine, IntcodeState, IntcodeInput, IntcodeOutput};
+
+// Everything a run changes, leaving configuration like breakpoints and limits alone
+#[derive(Clone)]
+pub struct IntcodeSnapshot<I, O> {
+    state: IntcodeState,
+    instruction_pointer: usize,
+    relative_base: i64,
+    memory: Vec<i64>,
+    produced_outputs: usize,
+    input_log: Option<Vec<i64>>,
+    ascii_answer: Option<i64>,
+    input_handler: I,
+    output_handler: O,
+}
+
+impl<I, O> IntcodeMachine<I, O>
+where I: IntcodeInput + Clone,
+      O: IntcodeOutput + Clone,
+{
+    pub fn snapshot(&self) -> IntcodeSnapshot<I, O> {
+        IntcodeSnapshot {
+            state: self.state.clone(),
+            instruction_pointer: self.instruction_pointer,
+            relative_base: self.relative_base,
+            memory: self.memory.clone(),
+            produced_outputs: self.produced_outputs,
+            input_log: self.input_log.clone(),
+            ascii_answer: self.ascii_answer,
+            input_handler: self.input_handler.clone(),
+            output_handler: self.output_handler.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &IntcodeSnapshot<I, O>) {
+        self.state = snapshot.state.clone();
+        self.instruction_pointer = snapshot.instruction_pointer;
+        self.relative_base = snapshot.relative_base;
+        self.memory.clone_from(&snapshot.memory);
+        self.produced_outputs = snapshot.produced_outputs;
+        self.input_log.clone_from(&snapshot.input_log);
+        self.ascii_answer = snapshot.ascii_answer;
+        self.input_handler = snapshot.input_handler.clone();
+        self.output_handler = snapshot.output_handler.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::IntcodeEvent;
+    use super::*;
+
+    #[test]
+    fn test_restore_replays_continuation() {
+        // Outputs the running total of its inputs after each one
+        let program = [3,20,1,20,21,21,4,21,1105,1,0];
+        let mut machine = IntcodeMachine::new_with_inputs(&program, vec![1, 2, 3, 4]);
+
+        assert_eq!(machine.step_until_event(), IntcodeEvent::Produced(1));
+        let snapshot = machine.snapshot();
+
+        let first = machine.resume();
+        let first_outputs = machine.drain_output();
+        assert_eq!(first, IntcodeEvent::AwaitingInput);
+        assert_eq!(first_outputs, vec![1, 3, 6, 10]);
+
+        for kept in [snapshot.clone(), snapshot] {
+            machine.restore(&kept);
+            assert_eq!(machine.resume(), first);
+            assert_eq!(machine.drain_output(), first_outputs);
+            assert_eq!(machine.read_memory_position(21), 10);
+        }
+    }
+}
@@ -6,11 +6,42 @@ pub enum IntcodeValue {
 }
 
 impl IntcodeValue {
-    pub fn evaluate(&self, memory: &[i64], relative_base: usize) -> i64 {
+    // Addresses past the end of memory haven't been written yet, so they read as 0.
+    // None means the parameter points below address 0.
+    pub fn evaluate(&self, memory: &[i64], relative_base: i64) -> Option<i64> {
         match self {
-            IntcodeValue::Position(position) => memory[*position],
+            IntcodeValue::Immediate(value) => Some(*value),
+            _ => self.address(relative_base).map(|address| memory.get(address).copied().unwrap_or(0)),
+        }
+    }
+
+    // The signed address a parameter points at, which relative mode can put below 0
+    pub fn target(&self, relative_base: i64) -> i64 {
+        match self {
+            IntcodeValue::Position(position) => *position as i64,
             IntcodeValue::Immediate(value) => *value,
-            IntcodeValue::Relative(offset) => memory[(relative_base as i64 + offset) as usize],
+            IntcodeValue::Relative(offset) => relative_base.saturating_add(*offset),
+        }
+    }
+
+    // Where a write lands, or None below address 0. The machine rejects immediate
+    // targets before writing, so treating them like position mode only affects disassembly.
+    pub fn address(&self, relative_base: i64) -> Option<usize> {
+        let target = self.target(relative_base);
+
+        if target < 0 {
+            None
+        } else {
+            Some(target as usize)
+        }
+    }
+
+    // The parameter mode digit and the raw word stored in the program
+    pub fn encode(&self) -> (i64, i64) {
+        match self {
+            IntcodeValue::Position(position) => (0, *position as i64),
+            IntcodeValue::Immediate(value) => (1, *value),
+            IntcodeValue::Relative(offset) => (2, *offset),
         }
     }
 }
@@ -28,3 +59,40 @@ impl std::fmt::Debug for IntcodeValue {
         write!(f, "{}{}", value, postfix)
     }
 }
+
+impl std::fmt::Display for IntcodeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use IntcodeValue::*;
+
+        match self {
+            Position(position) => write!(f, "[{}]", position),
+            Immediate(value) => write!(f, "{}", value),
+            Relative(offset) => write!(f, "@{}", offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(IntcodeValue::Position(1).to_string(), "[1]");
+        assert_eq!(IntcodeValue::Immediate(-4).to_string(), "-4");
+        assert_eq!(IntcodeValue::Relative(0).to_string(), "@0");
+
+        assert_eq!(format!("{:?}", IntcodeValue::Position(1)), "1p");
+    }
+
+    #[test]
+    fn test_negative_address() {
+        let memory = [7, 8, 9];
+
+        assert_eq!(IntcodeValue::Relative(-1).evaluate(&memory, 2), Some(8));
+        assert_eq!(IntcodeValue::Relative(-3).evaluate(&memory, 2), None);
+        assert_eq!(IntcodeValue::Relative(-3).target(2), -1);
+        assert_eq!(IntcodeValue::Relative(1).address(i64::MAX), Some(i64::MAX as usize));
+        assert_eq!(IntcodeValue::Immediate(-3).evaluate(&memory, 0), Some(-3));
+    }
+}
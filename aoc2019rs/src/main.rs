@@ -7,26 +7,32 @@ mod solutions;
 mod utils;
 
 fn main() -> Result<()> {
-    let day_num = std::env::args().nth(1).ok_or(anyhow!("Please provide a day number as the first argument"))?;
+    let day_num = std::env::args().nth(1).ok_or(anyhow!("Please provide a day number or \"all\" as the first argument"))?;
+
+    #[cfg(feature = "record-answers")]
+    {
+        if day_num == "record" {
+            return solutions::answers::record_answers();
+        }
+    }
     
-    println!("Running day #{}...", day_num);
-
-    let result = match day_num.as_ref() {
-        "1"  => solutions::day1::run()?,
-        "2"  => solutions::day2::run()?,
-        "3"  => solutions::day3::run()?,
-        "4"  => solutions::day4::run()?,
-        "5"  => solutions::day5::run()?,
-        "6"  => solutions::day6::run()?,
-        "7"  => solutions::day7::run()?,
-        "8"  => solutions::day8::run()?,
-        "9"  => solutions::day9::run()?,
-        "10" => solutions::day10::run()?,
-        "11" => solutions::day11::run()?,
-        "12" => solutions::day12::run()?,
-        _    => bail!("Invalid day number: {}", day_num),
+    if day_num == "all" {
+        print!("{}", solutions::run_all_report());
+        return Ok(());
+    }
+
+    let day: u8 = day_num.parse().map_err(|_| anyhow!("Expected a day number or \"all\", got {:?}", day_num))?;
+    let run = match solutions::SOLUTIONS.iter().find(|(number, _)| *number == day) {
+        Some((_, run)) => run,
+        None => {
+            let days: Vec<String> = solutions::SOLUTIONS.iter().map(|(number, _)| number.to_string()).collect();
+            bail!("Day {} isn't implemented, pick one of {}", day, days.join(", "))
+        },
     };
 
+    println!("Running day #{}...", day);
+    let result = run()?;
+
     println!("{}", result);
 
     Ok(())
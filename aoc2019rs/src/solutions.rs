@@ -1,3 +1,5 @@
+#[cfg(feature = "record-answers")]
+pub mod answers;
 pub mod day1;
 pub mod day2;
 pub mod day3;
@@ -9,4 +11,65 @@ pub mod day8;
 pub mod day9;
 pub mod day10;
 pub mod day11;
-pub mod day12;
\ No newline at end of file
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day25;
+
+use anyhow::Result;
+
+use std::time::{Duration, Instant};
+
+pub type DayRunner = fn() -> Result<String>;
+
+pub const SOLUTIONS: [(u8, DayRunner); 15] = [
+    (1, day1::run), (2, day2::run), (3, day3::run), (4, day4::run), (5, day5::run),
+    (6, day6::run), (7, day7::run), (8, day8::run), (9, day9::run), (10, day10::run),
+    (11, day11::run), (12, day12::run), (14, day14::run), (15, day15::run),
+    (25, day25::run),
+];
+
+// These wait on stdin, so they're left out of the report
+pub const INTERACTIVE_DAYS: [u8; 1] = [25];
+
+pub fn run_all_report() -> String {
+    let rows: Vec<String> = SOLUTIONS.iter()
+        .filter(|(day, _)| !INTERACTIVE_DAYS.contains(day))
+        .map(|(day, run)| {
+            let start = Instant::now();
+            let result = run();
+            report_row(*day, &result, start.elapsed())
+        })
+        .collect();
+
+    format!("{:>3} | {:<40} | {:>10}\n{}\n", "Day", "Answer", "Time", rows.join("\n"))
+}
+
+fn report_row(day: u8, result: &Result<String>, elapsed: Duration) -> String {
+    let answer = match result {
+        Ok(answer) => answer.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<&str>>().join("; "),
+        Err(e) => format!("Error: {}", e),
+    };
+
+    format!("{:>3} | {:<40} | {:>8}ms", day, answer, elapsed.as_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_report_row() {
+        let elapsed = Duration::from_millis(12);
+
+        assert_eq!(
+            report_row(7, &Ok(String::from("Part 1: 43812\nPart 2: 59597414\n")), elapsed),
+            "  7 | Part 1: 43812; Part 2: 59597414          |       12ms");
+        assert_eq!(
+            report_row(14, &Err(anyhow!("Not implemented")), elapsed),
+            " 14 | Error: Not implemented                   |       12ms");
+    }
+}
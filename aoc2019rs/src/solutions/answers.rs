@@ -0,0 +1,98 @@
+use anyhow::Result;
+
+use std::fs;
+use std::path::Path;
+
+use crate::solutions::{INTERACTIVE_DAYS, SOLUTIONS};
+
+/*
+    Only days whose result is formatted as "Part N: answer" lines can be recorded,
+    since a bare answer doesn't say which part it belongs to. Each answer replaces
+    the matching "// Part N:" comment and every other line is left untouched.
+*/
+pub fn record_answers() -> Result<()> {
+    for (day, run) in SOLUTIONS.iter().filter(|(day, _)| !INTERACTIVE_DAYS.contains(day)) {
+        let answers = match run() {
+            Ok(result) => parse_answers(&result),
+            Err(e) => {
+                println!("Day {}: skipped ({})", day, e);
+                continue;
+            },
+        };
+
+        if answers.is_empty() {
+            println!("Day {}: skipped (no \"Part N:\" answers in result)", day);
+            continue;
+        }
+
+        // Relative to the crate rather than wherever it's run from
+        let file_name = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("src/solutions/day{}.rs", day));
+        let source = fs::read_to_string(&file_name)?;
+        let updated = rewrite_answers(&source, &answers);
+
+        if updated != source {
+            fs::write(&file_name, updated)?;
+            println!("Day {}: updated {}", day, file_name.display());
+        } else {
+            println!("Day {}: unchanged", day);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_answers(result: &str) -> Vec<(usize, String)> {
+    result.lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("Part ")?;
+            let (part, answer) = rest.split_at(rest.find(':')?);
+            Some((part.parse().ok()?, String::from(answer[1..].trim())))
+        })
+        .collect()
+}
+
+fn rewrite_answers(source: &str, answers: &[(usize, String)]) -> String {
+    let mut result: String = source.lines()
+        .map(|line| {
+            let answer = answers.iter()
+                .find(|(part, _)| line.trim_end().starts_with(&format!("// Part {}:", part)));
+
+            match answer {
+                Some((part, answer)) => format!("// Part {}: {}\n", part, answer),
+                None => format!("{}\n", line),
+            }
+        })
+        .collect();
+
+    if !source.ends_with('\n') {
+        result.pop();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        assert_eq!(parse_answers("Part 1: 43812\nPart 2: 59597414"), vec![
+            (1, String::from("43812")),
+            (2, String::from("59597414")),
+        ]);
+        assert!(parse_answers("266").is_empty());
+        assert!(parse_answers("Part one: 5").is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_answers() {
+        let source = "use x;\n\n// Part 1: 266\n// Part 2: \n\nfn run() {\n    // Part 1: nested\n}\n";
+        let answers = vec![(1, String::from("266")), (2, String::from("42"))];
+
+        assert_eq!(
+            rewrite_answers(source, &answers),
+            "use x;\n\n// Part 1: 266\n// Part 2: 42\n\nfn run() {\n    // Part 1: nested\n}\n");
+        assert_eq!(rewrite_answers("// Part 1: 1", &[(1, String::from("2"))]), "// Part 1: 2");
+    }
+}
@@ -1,15 +1,28 @@
 use anyhow::Result;
 
-
 use crate::utils::input;
 
 pub fn run() -> Result<String> {
-    let input = input::read_input_lines_as::<i64>(1)?;
+    Ok(format!("Part 1: {}\nPart 2: {}", part1()?, part2()?))
+}
+
+pub fn part1() -> Result<String> {
+    let masses = input::read_input_lines_as::<i64>(1)?;
 
-    let result = input.into_iter()
+    let result: i64 = masses.into_iter()
         .map(fuel_by_weight)
-        .fold(0, total_fuel);
-    
+        .sum();
+
+    Ok(format!("{}", result))
+}
+
+pub fn part2() -> Result<String> {
+    let masses = input::read_input_lines_as::<i64>(1)?;
+
+    let result: i64 = masses.into_iter()
+        .map(fuel_required)
+        .sum();
+
     Ok(format!("{}", result))
 }
 
@@ -17,10 +30,46 @@ fn fuel_by_weight(weight: i64) -> i64 {
     (weight / 3) - 2
 }
 
-fn total_fuel(current_total: i64, weight: i64) -> i64 {
-    if weight <= 0 {
-        current_total
+// Fuel has mass too, so keep adding fuel for the fuel until it needs none
+fn fuel_required(mass: i64) -> i64 {
+    let fuel = fuel_by_weight(mass);
+    if fuel <= 0 {
+        0
     } else {
-        total_fuel(current_total + weight, fuel_by_weight(weight))
+        fuel + fuel_required(fuel)
+    }
+}
+
+// Part 1: 3252897
+// Part 2: 4876469
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuel_by_weight() {
+        assert_eq!(fuel_by_weight(12), 2);
+        assert_eq!(fuel_by_weight(14), 2);
+        assert_eq!(fuel_by_weight(1969), 654);
+        assert_eq!(fuel_by_weight(100756), 33583);
+    }
+
+    #[test]
+    fn test_fuel_required() {
+        assert_eq!(fuel_required(14), 2);
+        assert_eq!(fuel_required(1969), 966);
+        assert_eq!(fuel_required(100756), 50346);
+        assert_eq!(fuel_required(2), 0);
+    }
+
+    #[test]
+    fn day1_part1() {
+        assert_eq!(part1().unwrap(), "3252897");
+    }
+
+    #[test]
+    fn day1_part2() {
+        assert_eq!(part2().unwrap(), "4876469");
     }
 }
@@ -1,5 +1,166 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use nom::IResult;
+
+use std::str::FromStr;
+
+use crate::utils::input;
+
+// Part 1: 10189
+// Part 2: 
 
 pub fn run() -> Result<String> {
-    Ok(String::from("Not implemented"))
+    let moons = input::read_input_lines(12)?.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Moon::from_str(line))
+        .collect::<Result<Vec<Moon>>>()?;
+
+    Ok(format!("Part 1: {}", Moons::new(moons).energy_after(1000)))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Moon {
+    pub position: [i64; 3],
+    pub velocity: [i64; 3],
+}
+
+impl Moon {
+    pub fn new(position: [i64; 3]) -> Self {
+        Self { position, velocity: [0; 3] }
+    }
+
+    pub fn potential(&self) -> i64 {
+        self.position.iter().map(|p| p.abs()).sum()
+    }
+
+    pub fn kinetic(&self) -> i64 {
+        self.velocity.iter().map(|v| v.abs()).sum()
+    }
+
+    pub fn energy(&self) -> i64 {
+        self.potential() * self.kinetic()
+    }
+}
+
+impl FromStr for Moon {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        match moon(line.trim()) {
+            Ok(("", position)) => Ok(Moon::new(position)),
+            _ => Err(anyhow!("Invalid moon {:?}, expected e.g. \"<x=-1, y=0, z=2>\"", line)),
+        }
+    }
+}
+
+fn moon(input: &str) -> IResult<&str, [i64; 3]> {
+    use nom::{
+        bytes::complete::tag,
+        character::complete::{digit1, space0},
+        combinator::{map, map_res, opt, recognize},
+        sequence::{delimited, pair, preceded, tuple},
+    };
+
+    let coordinate = |name| preceded(
+        tuple((space0, tag(name), tag("="))),
+        map_res(recognize(pair(opt(tag("-")), digit1)), i64::from_str));
+
+    map(
+        delimited(
+            tag("<"),
+            tuple((coordinate("x"), preceded(tag(","), coordinate("y")), preceded(tag(","), coordinate("z")))),
+            tag(">")),
+        |(x, y, z)| [x, y, z],
+    )(input)
+}
+
+pub struct Moons {
+    moons: Vec<Moon>,
+}
+
+impl Moons {
+    pub fn new(moons: Vec<Moon>) -> Self {
+        Self { moons }
+    }
+
+    pub fn moons(&self) -> &[Moon] {
+        &self.moons
+    }
+
+    pub fn step(&mut self) {
+        for axis in 0..3 {
+            let positions: Vec<i64> = self.moons.iter().map(|moon| moon.position[axis]).collect();
+            for moon in self.moons.iter_mut() {
+                let pull: i64 = positions.iter().map(|other| (other - moon.position[axis]).signum()).sum();
+                moon.velocity[axis] += pull;
+            }
+        }
+
+        for moon in self.moons.iter_mut() {
+            for axis in 0..3 {
+                moon.position[axis] += moon.velocity[axis];
+            }
+        }
+    }
+
+    pub fn energy(&self) -> i64 {
+        self.moons.iter().map(Moon::energy).sum()
+    }
+
+    pub fn energy_after(&mut self, steps: usize) -> i64 {
+        for _ in 0..steps {
+            self.step();
+        }
+        self.energy()
+    }
+
+    // Axes never influence each other, so each one repeats on its own cycle
+    pub fn axis_state(&self, axis: usize) -> Vec<(i64, i64)> {
+        self.moons.iter()
+            .map(|moon| (moon.position[axis], moon.velocity[axis]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Moons {
+        let moons = ["<x=-1, y=0, z=2>", "<x=2, y=-10, z=-7>", "<x=4, y=-8, z=8>", "<x=3, y=5, z=-1>"].iter()
+            .map(|line| Moon::from_str(line).unwrap())
+            .collect();
+        Moons::new(moons)
+    }
+
+    #[test]
+    fn test_parse_moon() {
+        assert_eq!(Moon::from_str("<x=14, y=15, z=-2>").unwrap(), Moon::new([14, 15, -2]));
+        assert!(Moon::from_str("<x=14, y=15>").is_err());
+        assert!(Moon::from_str("x=1, y=2, z=3").is_err());
+    }
+
+    #[test]
+    fn test_energy_after() {
+        let mut moons = example();
+        assert_eq!(moons.energy_after(10), 179);
+
+        let first = &moons.moons()[0];
+        assert_eq!((first.potential(), first.kinetic(), first.energy()), (6, 6, 36));
+    }
+
+    #[test]
+    fn test_axis_state() {
+        let mut moons = example();
+        let initial = moons.axis_state(0);
+        assert_eq!(initial, vec![(-1, 0), (2, 0), (4, 0), (3, 0)]);
+
+        // The example's x axis repeats after 18 steps
+        moons.energy_after(18);
+        assert_eq!(moons.axis_state(0), initial);
+    }
+
+    #[test]
+    fn day12_part1() {
+        assert_eq!(run().unwrap(), "Part 1: 10189");
+    }
 }
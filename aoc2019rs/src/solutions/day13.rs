@@ -0,0 +1,128 @@
+use anyhow::{bail, Result};
+
+use crate::utils::grid::SparseGrid;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl Tile {
+    pub fn from_id(id: i64) -> Result<Self> {
+        Ok(match id {
+            0 => Tile::Empty,
+            1 => Tile::Wall,
+            2 => Tile::Block,
+            3 => Tile::Paddle,
+            4 => Tile::Ball,
+            _ => bail!("Invalid tile id: {}", id),
+        })
+    }
+}
+
+pub fn default_tile_char(tile: Tile) -> char {
+    match tile {
+        Tile::Empty => ' ',
+        Tile::Wall => '█',
+        Tile::Block => '#',
+        Tile::Paddle => '=',
+        Tile::Ball => 'o',
+    }
+}
+
+#[derive(Default)]
+pub struct Screen {
+    tiles: SparseGrid<Tile>,
+    score: i64,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.tiles.count_where(|&tile| tile == Tile::Block)
+    }
+
+    // Outputs come in (x, y, tile id) triples, with (-1, 0, score) updating the score
+    pub fn update(&mut self, outputs: &[i64]) -> Result<()> {
+        let triples = outputs.chunks_exact(3);
+        if !triples.remainder().is_empty() {
+            bail!("Screen outputs must come in triples, got {} values", outputs.len());
+        }
+
+        for triple in triples {
+            match triple {
+                [-1, 0, score] => self.score = *score,
+                [x, y, id] => self.tiles.set((*x, *y), Tile::from_id(*id)?),
+                _ => unreachable!(),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn render<F: Fn(Tile) -> char>(&self, tile_char: F) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = match self.tiles.bounds() {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+
+        (min_y..=max_y)
+            .map(|y| (min_x..=max_x)
+                .map(|x| tile_char(*self.tiles.get((x, y)).unwrap_or(&Tile::Empty)))
+                .collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen() -> Screen {
+        let mut screen = Screen::new();
+        screen.update(&[
+            0,0,1, 1,0,1, 2,0,1,
+            0,1,1, 1,1,4, 2,1,1,
+            0,2,1, 1,2,3, 2,2,2,
+            -1,0,42,
+        ]).unwrap();
+        screen
+    }
+
+    #[test]
+    fn test_render_default() {
+        let screen = screen();
+        assert_eq!(screen.render(default_tile_char), "███\n█o█\n█=#");
+        assert_eq!(screen.score(), 42);
+        assert_eq!(screen.block_count(), 1);
+    }
+
+    #[test]
+    fn test_render_custom() {
+        let ascii = |tile| match tile {
+            Tile::Wall => '+',
+            Tile::Ball => '*',
+            Tile::Paddle => '-',
+            tile => default_tile_char(tile),
+        };
+        assert_eq!(screen().render(ascii), "+++\n+*+\n+-#");
+    }
+
+    #[test]
+    fn test_update_errors() {
+        assert!(Screen::new().update(&[0, 0]).is_err());
+        assert!(Screen::new().update(&[0, 0, 5]).is_err());
+    }
+}
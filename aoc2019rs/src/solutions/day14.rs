@@ -0,0 +1,170 @@
+use anyhow::{anyhow, bail, Result};
+use nom::IResult;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+pub fn run() -> Result<String> {
+    bail!("Not implemented")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reaction {
+    pub inputs: Vec<(u64, String)>,
+    pub output: (u64, String),
+}
+
+pub type ReactionTable = HashMap<String, Reaction>;
+
+pub fn parse_table(lines: &[String]) -> Result<ReactionTable> {
+    lines.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_reaction(line).map(|reaction| (reaction.output.1.clone(), reaction)))
+        .collect()
+}
+
+// Orders chemicals so that each one comes after every chemical whose reaction
+// consumes it, so demand can be pushed from FUEL down to ORE in a single pass.
+pub fn topo_order(table: &ReactionTable) -> Result<Vec<String>> {
+    let mut consumers: BTreeMap<&str, usize> = BTreeMap::new();
+    for (output, reaction) in table {
+        consumers.entry(output).or_insert(0);
+        for (_, input) in &reaction.inputs {
+            *consumers.entry(input).or_insert(0) += 1;
+        }
+    }
+
+    let mut ready: BTreeSet<&str> = consumers.iter()
+        .filter(|(_, &count)| count == 0)
+        .map(|(&chemical, _)| chemical)
+        .collect();
+
+    let mut order = Vec::with_capacity(consumers.len());
+    while let Some(chemical) = ready.iter().next().cloned() {
+        ready.remove(chemical);
+        order.push(String::from(chemical));
+
+        if let Some(reaction) = table.get(chemical) {
+            for (_, input) in &reaction.inputs {
+                let count = consumers.get_mut(input.as_str()).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(input);
+                }
+            }
+        }
+    }
+
+    if order.len() != consumers.len() {
+        bail!("Reaction table contains a cycle");
+    }
+
+    Ok(order)
+}
+
+pub fn parse_reaction(line: &str) -> Result<Reaction> {
+    match reaction(line) {
+        Ok(("", reaction)) => Ok(reaction),
+        Ok((remaining, _)) => bail!("Unexpected trailing input {:?} in reaction {:?}", remaining, line),
+        Err(_) => Err(anyhow!("Invalid reaction {:?}, expected e.g. \"7 A, 1 B => 1 C\"", line)),
+    }
+}
+
+fn reaction(input: &str) -> IResult<&str, Reaction> {
+    use nom::{
+        bytes::complete::tag,
+        character::complete::{multispace0, space0},
+        combinator::map,
+        multi::separated_nonempty_list,
+        sequence::{delimited, separated_pair},
+    };
+
+    map(
+        delimited(
+            multispace0,
+            separated_pair(
+                separated_nonempty_list(delimited(space0, tag(","), space0), chemical),
+                delimited(space0, tag("=>"), space0),
+                chemical),
+            multispace0),
+        |(inputs, output)| Reaction { inputs, output },
+    )(input)
+}
+
+fn chemical(input: &str) -> IResult<&str, (u64, String)> {
+    use nom::{
+        character::complete::{alpha1, digit1, space1},
+        combinator::{map, map_res},
+        sequence::separated_pair,
+    };
+
+    separated_pair(
+        map_res(digit1, u64::from_str),
+        space1,
+        map(alpha1, String::from),
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chemical(quantity: u64, name: &str) -> (u64, String) {
+        (quantity, String::from(name))
+    }
+
+    #[test]
+    fn test_parse_reaction() {
+        assert_eq!(parse_reaction("7 A, 1 B => 1 C").unwrap(), Reaction {
+            inputs: vec![chemical(7, "A"), chemical(1, "B")],
+            output: chemical(1, "C"),
+        });
+
+        assert_eq!(parse_reaction("10 ORE => 10 A").unwrap(), Reaction {
+            inputs: vec![chemical(10, "ORE")],
+            output: chemical(10, "A"),
+        });
+    }
+
+    #[test]
+    fn test_parse_reaction_whitespace() {
+        assert_eq!(parse_reaction("  7 A ,1 B=>1  FUEL \n").unwrap(), Reaction {
+            inputs: vec![chemical(7, "A"), chemical(1, "B")],
+            output: chemical(1, "FUEL"),
+        });
+    }
+
+    fn table(lines: &[&str]) -> ReactionTable {
+        let lines: Vec<String> = lines.iter().map(|line| String::from(*line)).collect();
+        parse_table(&lines).unwrap()
+    }
+
+    #[test]
+    fn test_topo_order() {
+        let reactions = table(&[
+            "10 ORE => 10 A",
+            "1 ORE => 1 B",
+            "7 A, 1 B => 1 C",
+            "7 A, 1 C => 1 D",
+            "7 A, 1 D => 1 E",
+            "7 A, 1 E => 1 FUEL",
+        ]);
+
+        assert_eq!(topo_order(&reactions).unwrap(), vec!["FUEL", "E", "D", "C", "A", "B", "ORE"]);
+    }
+
+    #[test]
+    fn test_topo_order_cycle() {
+        let reactions = table(&["1 ORE, 1 B => 1 A", "1 A => 1 B", "1 A => 1 FUEL"]);
+        assert!(topo_order(&reactions).is_err());
+    }
+
+    #[test]
+    fn test_parse_reaction_malformed() {
+        assert!(parse_reaction("").is_err());
+        assert!(parse_reaction("7 A, 1 B").is_err());
+        assert!(parse_reaction("7 A, => 1 C").is_err());
+        assert!(parse_reaction("A 7 => 1 C").is_err());
+        assert!(parse_reaction("7 A => 1 C, 2 D").is_err());
+    }
+}
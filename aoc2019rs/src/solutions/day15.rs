@@ -0,0 +1,87 @@
+use anyhow::{bail, Result};
+
+use std::collections::VecDeque;
+
+use crate::intcode::{IntcodeMachine, IntcodeBlockingInput, IntcodeHistoryOutput};
+use crate::utils::grid::SparseGrid;
+
+type Droid = IntcodeMachine<IntcodeBlockingInput, IntcodeHistoryOutput>;
+
+pub fn run() -> Result<String> {
+    bail!("Not implemented")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tile {
+    Wall,
+    Open,
+    Oxygen,
+}
+
+const DIRECTIONS: [(i64, (i64, i64)); 4] = [
+    (1, (0, -1)),
+    (2, (0, 1)),
+    (3, (-1, 0)),
+    (4, (1, 0)),
+];
+
+pub fn explore_with_snapshots(mut machine: Droid) -> SparseGrid<Tile> {
+    let mut grid = SparseGrid::new();
+    let mut frontier = VecDeque::new();
+
+    // Run up to the first movement request so every snapshot is waiting on input
+    machine.run();
+    grid.set((0, 0), Tile::Open);
+    frontier.push_back(((0, 0), machine));
+
+    while let Some((position, snapshot)) = frontier.pop_front() {
+        let unexplored: Vec<(i64, (i64, i64))> = DIRECTIONS.iter()
+            .map(|(command, offset)| (*command, (position.0 + offset.0, position.1 + offset.1)))
+            .filter(|(_, next_position)| !grid.contains(*next_position))
+            .collect();
+
+        let commands: Vec<i64> = unexplored.iter().map(|(command, _)| *command).collect();
+        for ((_, next_position), (_, droid, status)) in unexplored.into_iter().zip(snapshot.fork_on_inputs(&commands)) {
+            match status.expect("Droid didn't report a status") {
+                0 => grid.set(next_position, Tile::Wall),
+                1 => {
+                    grid.set(next_position, Tile::Open);
+                    frontier.push_back((next_position, droid));
+                },
+                2 => {
+                    grid.set(next_position, Tile::Oxygen);
+                    frontier.push_back((next_position, droid));
+                },
+                status => panic!("Invalid droid status: {}", status),
+            }
+        }
+    }
+
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A droid stuck in a three cell east-west corridor with oxygen at the east end
+    const CORRIDOR: [i64; 61] = [
+        3,100,1008,100,4,102,1006,102,33,1007,101,2,102,1006,102,56,1001,101,1,101,
+        1008,101,2,102,1001,102,1,102,4,102,1105,1,0,1008,100,3,102,1006,102,56,1007,
+        101,1,102,1005,102,56,1001,101,-1,101,104,1,1105,1,0,104,0,1105,1,0,
+    ];
+
+    #[test]
+    fn test_explore_with_snapshots() {
+        let grid = explore_with_snapshots(IntcodeMachine::new_blocking_machine(&CORRIDOR));
+
+        assert_eq!(grid.len(), 11);
+        assert_eq!(grid.get((0, 0)), Some(&Tile::Open));
+        assert_eq!(grid.get((1, 0)), Some(&Tile::Open));
+        assert_eq!(grid.get((2, 0)), Some(&Tile::Oxygen));
+        assert_eq!(grid.get((-1, 0)), Some(&Tile::Wall));
+        assert_eq!(grid.get((3, 0)), Some(&Tile::Wall));
+        assert_eq!(grid.get((1, -1)), Some(&Tile::Wall));
+        assert_eq!(grid.get((1, 1)), Some(&Tile::Wall));
+    }
+}
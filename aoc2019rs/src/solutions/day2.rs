@@ -1,15 +1,28 @@
 use anyhow::{bail, Result};
 
 use crate::utils::input;
-use crate::intcode::IntcodeMachine;
+use crate::intcode::{IntcodeMachine, IntcodeError};
+
+// The program has no jumps, so anything running this long has been corrupted by its inputs
+const MAX_INSTRUCTIONS: u64 = 10_000;
 
 pub fn run() -> Result<String> {
-    let program = input::read_input_list_as::<i64>(2, b',')?;
+    Ok(format!("Part 1: {}\nPart 2: {}", part1()?, part2()?))
+}
+
+pub fn part1() -> Result<String> {
+    let program = input::read_input_cached(2)?;
+
+    Ok(format!("{}", run_test(&program, 12, 2)?))
+}
+
+pub fn part2() -> Result<String> {
+    let program = input::read_input_cached(2)?;
     
     for noun in 0..=99 {
         for verb in 0..=99 {
-            let result = run_test(&program, noun, verb);
-            if result == 19690720 {
+            // Combinations that crash the program can't be the answer
+            if let Ok(19690720) = run_test(&program, noun, verb) {
                 return Ok(format!("{}", 100 * noun + verb));
             }
             
@@ -19,13 +32,11 @@ pub fn run() -> Result<String> {
     bail!("Couldn't find inputs with output 19690720")
 }
 
-fn run_test(program: &[i64], noun: i64, verb: i64) -> i64 {
-    let mut machine = IntcodeMachine::new_console_machine(&program);
-    machine.write_memory(1, noun);
-    machine.write_memory(2, verb);
-    machine.run();
+fn run_test(program: &[i64], noun: i64, verb: i64) -> Result<i64, IntcodeError> {
+    let mut machine = IntcodeMachine::new_with_overrides(program, &[(1, noun), (2, verb)])?;
+    machine.run_with_limit(MAX_INSTRUCTIONS)?;
 
-    machine.read_memory_position(0)
+    Ok(machine.read_memory_position(0))
 }
 
 // Part 1: 7594646
@@ -37,36 +48,11 @@ mod tests {
 
     #[test]
     fn day2_part1() {
-        let input = day2_input();
-        assert_eq!(run_day2_test(&input, 12, 2), 7594646);
+        assert_eq!(part1().unwrap(), "7594646");
     }
 
     #[test]
     fn day2_part2() {
-        let input = day2_input();
-        for noun in 0..=99 {
-            for verb in 0..=99 {
-                let result = run_day2_test(&input, noun, verb);
-                if result == 19690720 {
-                    return assert_eq!(100 * noun + verb, 3376);
-                }
-                
-            }
-        }
-
-        assert!(false)
-    }
-
-    fn day2_input() -> Vec<i64> {
-        crate::utils::input::read_input_list_as::<i64>(2, b',').unwrap()
-    }
-
-    fn run_day2_test(program: &[i64], noun: i64, verb: i64) -> i64 {
-        let mut machine = IntcodeMachine::new_console_machine(program);
-        machine.write_memory(1, noun);
-        machine.write_memory(2, verb);
-        machine.run();
-    
-        machine.read_memory_position(0)
+        assert_eq!(part2().unwrap(), "3376");
     }
 }
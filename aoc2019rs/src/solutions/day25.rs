@@ -0,0 +1,13 @@
+use anyhow::Result;
+
+use crate::utils::input;
+use crate::intcode::IntcodeMachine;
+
+// The password is only ever printed by the game itself, so there's no answer to return
+pub fn run() -> Result<String> {
+    let program = input::read_input_cached(25)?;
+    let mut machine = IntcodeMachine::new_with_inputs(&program, vec![]);
+    machine.run_interactive()?;
+
+    Ok(String::from("Adventure over"))
+}
@@ -4,7 +4,8 @@ use std::str::FromStr;
 use std::collections::HashSet;
 use std::ops::Add;
 
-use crate::utils::input;
+use crate::utils::{geometry, input};
+use crate::utils::grid::Point;
 
 // Part 1: 266
 // Part 2: 
@@ -36,7 +37,7 @@ fn solve(wire1: Vec<WireSegment>, wire2: Vec<WireSegment>) -> i64 {
 }
 
 fn distance(cell: &(i64, i64)) -> i64 {
-    cell.0.abs() + cell.1.abs()
+    geometry::manhattan_from_origin(Point::new(cell.0, cell.1))
 }
 
 fn add_wire(grid: &mut HashSet<(i64, i64)>, wire: Vec<WireSegment>) {
@@ -1,35 +1,79 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 
 use crate::utils::input;
-use crate::intcode::helpers;
+use crate::intcode::IntcodeMachine;
 
 // Part 1: 9025675
 // Part 2: 11981754
 
 pub fn run() -> Result<String> {
-    let program = input::read_input_list_as::<i64>(5, b',')?;
-
-    let part1 = helpers::process_input(&program, &[1])
-        .last()
-        .unwrap()
-        .clone();
-    let part2 = helpers::process_input(&program, &[5])
-        .last()
-        .unwrap()
-        .clone();
-
-    Ok(format!("Part 1: {}\nPart 2: {}", part1, part2))
+    Ok(format!("Part 1: {}\nPart 2: {}", part1()?, part2()?))
+}
+
+// Every test before the diagnostic code outputs 0 when it passes
+pub fn part1() -> Result<String> {
+    let outputs = run_diagnostic(1)?;
+    let (diagnostic, checks) = outputs.split_last().ok_or_else(|| anyhow!("No diagnostic code was output"))?;
+
+    if let Some(failed) = checks.iter().position(|&check| check != 0) {
+        bail!("Diagnostic check {} failed with {}", failed, checks[failed]);
+    }
+
+    Ok(format!("{}", diagnostic))
+}
+
+pub fn part2() -> Result<String> {
+    let outputs = run_diagnostic(5)?;
+    let diagnostic = outputs.last().ok_or_else(|| anyhow!("No diagnostic code was output"))?;
+
+    Ok(format!("{}", diagnostic))
+}
+
+fn run_diagnostic(system_id: i64) -> Result<Vec<i64>> {
+    let program = input::read_input_cached(5)?;
+    let mut machine = IntcodeMachine::new_with_inputs(&program, vec![system_id]);
+    machine.try_run()?;
+    machine.ensure_halted()?;
+
+    Ok(machine.drain_output())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::intcode::helpers;
+    use super::*;
+
+    use crate::intcode::{IntcodeOutput, helpers};
 
     #[test]
     fn day5_part1() {
+        assert_eq!(part1().unwrap(), "9025675");
+    }
+
+    #[test]
+    fn day5_part2() {
+        assert_eq!(part2().unwrap(), "11981754");
+    }
+
+    #[test]
+    fn day5_halts_cleanly() {
         let program = day5_input();
-        let result = helpers::process_input(&program, &[1]).last().unwrap().clone();
-        assert_eq!(result, "9025675");
+        for input in &[1, 5] {
+            let mut machine = IntcodeMachine::new_automated_machine(&program, &[*input]);
+            machine.run();
+            machine.ensure_halted().unwrap();
+        }
+    }
+
+    #[test]
+    fn day5_queued_diagnostic() {
+        let mut machine = IntcodeMachine::new_with_inputs(&day5_input(), vec![1]);
+        machine.run();
+        machine.ensure_halted().unwrap();
+
+        let (diagnostic, checks) = machine.output_handler().buffered().split_last().unwrap();
+        assert_eq!(*diagnostic, 9025675);
+        assert!(!checks.is_empty());
+        assert!(checks.iter().all(|&check| check == 0));
     }
 
     fn day5_input() -> Vec<i64> {
@@ -38,29 +82,29 @@ mod tests {
 
     #[test]
     fn day5_comparison_tests() {
-        assert_eq!(helpers::process_input(&[3,9,8,9,10,9,4,9,99,-1,8], &[7]).last().unwrap().clone(), "0");
-        assert_eq!(helpers::process_input(&[3,9,8,9,10,9,4,9,99,-1,8], &[8]).last().unwrap().clone(), "1");
+        assert_eq!(helpers::process_input(&[3,9,8,9,10,9,4,9,99,-1,8], &[7]).last(), Some(&0));
+        assert_eq!(helpers::process_input(&[3,9,8,9,10,9,4,9,99,-1,8], &[8]).last(), Some(&1));
         
-        assert_eq!(helpers::process_input(&[3,9,7,9,10,9,4,9,99,-1,8], &[7]).last().unwrap().clone(), "1");
-        assert_eq!(helpers::process_input(&[3,9,7,9,10,9,4,9,99,-1,8], &[9]).last().unwrap().clone(), "0");
+        assert_eq!(helpers::process_input(&[3,9,7,9,10,9,4,9,99,-1,8], &[7]).last(), Some(&1));
+        assert_eq!(helpers::process_input(&[3,9,7,9,10,9,4,9,99,-1,8], &[9]).last(), Some(&0));
 
-        assert_eq!(helpers::process_input(&[3,3,1108,-1,8,3,4,3,99], &[7]).last().unwrap().clone(), "0");
-        assert_eq!(helpers::process_input(&[3,3,1108,-1,8,3,4,3,99], &[8]).last().unwrap().clone(), "1");
+        assert_eq!(helpers::process_input(&[3,3,1108,-1,8,3,4,3,99], &[7]).last(), Some(&0));
+        assert_eq!(helpers::process_input(&[3,3,1108,-1,8,3,4,3,99], &[8]).last(), Some(&1));
 
-        assert_eq!(helpers::process_input(&[3,3,1107,-1,8,3,4,3,99], &[7]).last().unwrap().clone(), "1");
-        assert_eq!(helpers::process_input(&[3,3,1107,-1,8,3,4,3,99], &[9]).last().unwrap().clone(), "0");
+        assert_eq!(helpers::process_input(&[3,3,1107,-1,8,3,4,3,99], &[7]).last(), Some(&1));
+        assert_eq!(helpers::process_input(&[3,3,1107,-1,8,3,4,3,99], &[9]).last(), Some(&0));
     }
 
     #[test]
     fn day5_jump_position_tests() {
-        assert_eq!(helpers::process_input(&[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9], &[0]).last().unwrap().clone(), "0");
-        assert_eq!(helpers::process_input(&[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9], &[1]).last().unwrap().clone(), "1");
+        assert_eq!(helpers::process_input(&[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9], &[0]).last(), Some(&0));
+        assert_eq!(helpers::process_input(&[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9], &[1]).last(), Some(&1));
     }
 
     #[test]
     fn day5_jump_immediate_tests() {
-        assert_eq!(helpers::process_input(&[3,3,1105,-1,9,1101,0,0,12,4,12,99,1], &[0]).last().unwrap().clone(), "0");
-        assert_eq!(helpers::process_input(&[3,3,1105,-1,9,1101,0,0,12,4,12,99,1], &[1]).last().unwrap().clone(), "1");
+        assert_eq!(helpers::process_input(&[3,3,1105,-1,9,1101,0,0,12,4,12,99,1], &[0]).last(), Some(&0));
+        assert_eq!(helpers::process_input(&[3,3,1105,-1,9,1101,0,0,12,4,12,99,1], &[1]).last(), Some(&1));
     } 
 
     #[test]
@@ -68,20 +112,20 @@ mod tests {
         assert_eq!(helpers::process_input(
             &[3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,
               1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,
-              999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99], &[7]).last().unwrap().clone(), 
-            "999");
+              999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99], &[7]).last(), 
+            Some(&999));
 
         assert_eq!(helpers::process_input(
             &[3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,
               1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,
-              999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99], &[8]).last().unwrap().clone(), 
-            "1000");
+              999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99], &[8]).last(), 
+            Some(&1000));
 
         assert_eq!(helpers::process_input(
             &[3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,
               1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,
-              999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99], &[9]).last().unwrap().clone(), 
-            "1001");
+              999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99], &[9]).last(), 
+            Some(&1001));
         
     }    
 }
\ No newline at end of file
@@ -1,86 +1,79 @@
 use anyhow::Result;
 
-use std::str::FromStr;
-
-use crate::utils::{input, math};
-use crate::intcode::{IntcodeMachine, IntcodeState, IntcodeOutput, helpers};
+use crate::utils::{combinatorics, input, math};
+use crate::intcode::{IntcodeMachine, IntcodeState};
 
 // Part 1: 43812
 // Part 2: 59597414
 
 pub fn run() -> Result<String> {
-    let program = input::read_input_list_as::<i64>(7, b',')?;
+    Ok(format!("Part 1: {}\nPart 2: {}", part1()?, part2()?))
+}
 
-    Ok(format!("Part 1: {}\nPart 2: {}", run_day_1(&program), run_day_2(&program)))
+pub fn part1() -> Result<String> {
+    let program = input::read_input_cached(7)?;
+    Ok(format!("{}", run_day_1(&program)))
 }
 
-fn run_day_1(program: &[i64]) -> i64 {
-    let phase_permutations = math::permutations_cloned::<i64>(&[0, 1, 2, 3, 4]);
+pub fn part2() -> Result<String> {
+    let program = input::read_input_cached(7)?;
+    Ok(format!("{}", run_day_2(&program)))
+}
 
-    phase_permutations.into_iter()
-        .map(|permutation| run_day_1_phase_permutation(&program, &permutation))
+fn run_day_1(program: &[i64]) -> i64 {
+    combinatorics::permutations(&[0, 1, 2, 3, 4]).into_iter()
+        .map(|permutation| run_amplifier_chain(program, &permutation))
         .max()
         .unwrap()
 }
 
-fn run_day_1_phase_permutation(program: &[i64], phases: &[i64]) -> i64 {
-    let mut next_input = 0;
-    for phase in phases {
-        let output = helpers::process_input(&program.clone(), &[*phase, next_input])
-            .last()
-            .unwrap()
-            .clone();
-        next_input = i64::from_str(&output).unwrap();
+// Each amplifier runs once, passing its single output on to the next
+pub fn run_amplifier_chain(program: &[i64], phases: &[i64]) -> i64 {
+    let mut signal = 0;
+    for &phase in phases {
+        let mut amplifier = IntcodeMachine::new_with_inputs(program, vec![phase, signal]);
+        amplifier.run();
+        signal = amplifier.last_output().expect("No output available");
     }
 
-    next_input
+    signal
 }
 
 fn run_day_2(program: &[i64]) -> i64 {
     let phase_permutations = math::permutations_cloned::<i64>(&[5, 6, 7, 8, 9]);
     
     phase_permutations.into_iter()
-        .map(|permutation| run_day_2_phase_permutation(&program, &permutation))
+        .map(|permutation| run_amplifier_loop(&program, &permutation))
         .max()
         .unwrap()
 }
 
-fn run_day_2_phase_permutation(program: &[i64], phases: &[i64]) -> i64 {
-    let mut amplifiers = vec![
-        IntcodeMachine::new_blocking_machine(&program),
-        IntcodeMachine::new_blocking_machine(&program),
-        IntcodeMachine::new_blocking_machine(&program),
-        IntcodeMachine::new_blocking_machine(&program),
-        IntcodeMachine::new_blocking_machine(&program),
-    ];
-
-    // Initialize with phase
-    for (i, amp) in amplifiers.iter_mut().enumerate() {
-        amp.run();
-        amp.input(phases[i]);
-    }
-
-    let mut next_input = 0;
-    loop {
-        for amp in amplifiers.iter_mut() {
-            amp.run();
-            amp.input(next_input);
-            amp.run();
-
-            let output = amp.output_handler()
-                .last_output()
-                .expect("No output available")
-                .to_owned();
-
-            next_input = i64::from_str(&output).unwrap();
-        }
-        if amplifiers[4].state() == &IntcodeState::Halted {
-            break;
+// Feeds the last amplifier's outputs back into the first until it halts. Each
+// amplifier runs until it needs input, so none of them wait on one another forever.
+pub fn run_amplifier_loop(program: &[i64], phases: &[i64]) -> i64 {
+    let mut amplifiers: Vec<_> = phases.iter()
+        .map(|&phase| IntcodeMachine::new_with_inputs(program, vec![phase]))
+        .collect();
+    amplifiers[0].push_input(0);
+
+    let count = amplifiers.len();
+    let mut thrust = 0;
+    while amplifiers[count - 1].state() != &IntcodeState::Halted {
+        let mut progressed = false;
+        for index in 0..count {
+            amplifiers[index].resume();
+            let outputs = amplifiers[index].drain_output();
+            progressed |= !outputs.is_empty();
+
+            if index == count - 1 {
+                thrust = outputs.last().copied().unwrap_or(thrust);
+            }
+            amplifiers[(index + 1) % count].push_inputs(outputs);
         }
+        assert!(progressed || amplifiers[count - 1].state() == &IntcodeState::Halted, "Amplifiers stopped producing output");
     }
 
-    let last_output = amplifiers[4].output_handler().last_output().unwrap().to_owned();
-    i64::from_str(&last_output).unwrap()
+    thrust
 }
 
 #[cfg(test)] 
@@ -89,8 +82,37 @@ mod tests {
 
     #[test]
     fn day7_part1_test() {
-        let program = input::read_input_list_as::<i64>(7, b',').unwrap();
-        let result = run_day_1(&program);
-        assert_eq!(result, 43812);
+        assert_eq!(part1().unwrap(), "43812");
+    }
+
+    #[test]
+    fn day7_part2_test() {
+        assert_eq!(part2().unwrap(), "59597414");
+    }
+
+    #[test]
+    fn day7_amplifier_chain_examples() {
+        let program = [3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0];
+        assert_eq!(run_amplifier_chain(&program, &[4,3,2,1,0]), 43210);
+        assert_eq!(run_day_1(&program), 43210);
+
+        let program = [3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0];
+        assert_eq!(run_day_1(&program), 54321);
+
+        let program = [3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0];
+        assert_eq!(run_day_1(&program), 65210);
+    }
+
+    #[test]
+    fn day7_amplifier_loop_examples() {
+        let program = [3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5];
+        assert_eq!(run_amplifier_loop(&program, &[9,8,7,6,5]), 139629729);
+        assert_eq!(run_day_2(&program), 139629729);
+
+        let program = [3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,
+            -5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,
+            1005,56,6,99,0,0,0,0,10];
+        assert_eq!(run_amplifier_loop(&program, &[9,7,8,5,6]), 18216);
+        assert_eq!(run_day_2(&program), 18216);
     }
 }
@@ -1,43 +1,73 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 
 use crate::utils::input;
-use crate::intcode::helpers;
+use crate::intcode::IntcodeMachine;
+
+// Part 1: 3100786347
+// Part 2: 87023
 
 pub fn run() -> Result<String> {
-    let program = input::read_input_list_as::<i64>(9, b',')?;
-
-    
-    let(instructions, output) = helpers::debug_process_input(&program, &[1]);
-    dbg!(instructions);
-    dbg!(output);
-    
-    Ok(format!("Part 1: {:?}\nPart 2: {}\n", 0, 0))
+    Ok(format!("Part 1: {}\nPart 2: {}", part1()?, part2()?))
+}
+
+// Anything other than the keycode alone is a list of the opcodes that failed the self-test
+pub fn part1() -> Result<String> {
+    run_boost(1)
+}
+
+pub fn part2() -> Result<String> {
+    run_boost(2)
+}
+
+fn run_boost(mode: i64) -> Result<String> {
+    let program = input::read_input_cached(9)?;
+    let mut machine = IntcodeMachine::new_with_inputs(&program, vec![mode]);
+    machine.try_run()?;
+    machine.ensure_halted()?;
+
+    match machine.drain_output().as_slice() {
+        [value] => Ok(format!("{}", value)),
+        [] => Err(anyhow!("BOOST produced no output")),
+        outputs => bail!("BOOST self-test failed for opcodes {:?}", outputs),
+    }
 }
 
-/*
-
-[src\solutions\day9.rs:11] instructions = [
-    Mul 34463338i 34463338i 63,
-    Lst 63p 34463338i 63,
-    JmT 63p 53i,
-    Mul 3i 1i 1000,
-    Srb 988i,
-    Srb 12r,
-    Srb 1000p,
-    Srb 6r,
-    Srb 3r,
-    Inp 0,
-    Eqt 1000p 1i 63,
-    JmT 63p 65i,
-    Eqt 1000p 2i 63,
-    JmT 63p 904i,
-    Eqt 1000p 0i 63,
-    JmT 63p 58i,
-    *Out 25p,
-    Out 0i,
-    Halt,
-]
-
-rel: 1000
-
-*/
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outputs(program: &[i64]) -> Vec<i64> {
+        let mut machine = IntcodeMachine::new_with_inputs(program, vec![]);
+        machine.run();
+        machine.ensure_halted().unwrap();
+        machine.drain_output()
+    }
+
+    #[test]
+    fn day9_part1() {
+        assert_eq!(part1().unwrap(), "3100786347");
+    }
+
+    #[test]
+    fn day9_part2() {
+        assert_eq!(part2().unwrap(), "87023");
+    }
+
+    #[test]
+    fn day9_quine() {
+        let program = [109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
+        assert_eq!(outputs(&program), program.to_vec());
+    }
+
+    #[test]
+    fn day9_sixteen_digits() {
+        let output = outputs(&[1102,34915192,34915192,7,4,7,99,0]);
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].to_string().len(), 16);
+    }
+
+    #[test]
+    fn day9_large_number() {
+        assert_eq!(outputs(&[104,1125899906842624,99]), vec![1125899906842624]);
+    }
+}
@@ -1,5 +1,10 @@
+pub mod combinatorics;
 pub mod conversion;
+pub mod geometry;
 pub mod graph;
+pub mod grid;
 pub mod input;
 pub mod math;
 pub mod parser;
+pub mod pathfinding;
+pub mod search;
@@ -0,0 +1,27 @@
+use crate::utils::math;
+
+// Repeated values would otherwise give the same arrangement more than once.
+// The result is sorted, and empty when the input is.
+pub fn permutations<T: Clone + Ord>(input: &[T]) -> Vec<Vec<T>> {
+    let mut result = math::permutations_cloned(input);
+    result.sort();
+    result.dedup();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permutations() {
+        let distinct = permutations(&[1, 2, 3]);
+        assert_eq!(distinct.len(), 6);
+        assert!(distinct.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(distinct.len(), math::permutations(&[1, 2, 3]).len());
+
+        assert_eq!(permutations(&[1, 1, 2]), vec![vec![1, 1, 2], vec![1, 2, 1], vec![2, 1, 1]]);
+        assert_eq!(permutations(&[7, 7]), vec![vec![7, 7]]);
+        assert_eq!(permutations::<i64>(&[]), Vec::<Vec<i64>>::new());
+    }
+}
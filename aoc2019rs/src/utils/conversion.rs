@@ -1,15 +1,89 @@
+// Most significant digit first, so 1002 becomes [1, 0, 0, 2]. Only the magnitude
+// is converted, so -1002 gives the same digits.
 pub fn i64_into_digits(value: &i64) -> Vec<usize> {
-    usize_into_digits(&(*value as usize))
+    usize_into_digits(&(value.unsigned_abs() as usize))
+}
+
+// Inverse of i64_into_digits, so also most significant digit first. No digits at all is 0.
+pub fn digits_into_i64(digits: &[usize]) -> i64 {
+    digits.iter().fold(0, |value, &digit| value * 10 + digit as i64)
 }
 
 pub fn usize_into_digits(value: &usize) -> Vec<usize> {
+    usize_into_digits_le(*value).into_iter().rev().collect()
+}
+
+// Least significant digit first, so 1002 becomes [2, 0, 0, 1]. Also magnitude only.
+pub fn i64_into_digits_le(value: i64) -> Vec<usize> {
+    usize_into_digits_le(value.unsigned_abs() as usize)
+}
+
+// Modes of the first, second and third parameters, taken from the hundreds,
+// thousands and ten-thousands places of an instruction word
+pub fn parameter_modes(word: i64) -> [u8; 3] {
+    debug_assert!(word >= 0, "Instruction word {} is negative", word);
+
+    // Decoded on every instruction, so this stays arithmetic rather than collecting digits
+    let mut modes = [0; 3];
+    let mut remaining = word / 100;
+    for mode in modes.iter_mut() {
+        *mode = (remaining % 10) as u8;
+        remaining /= 10;
+    }
+    modes
+}
+
+fn usize_into_digits_le(value: usize) -> Vec<usize> {
     let mut result = Vec::with_capacity(6);
 
-    let mut current_value = *value;
+    let mut current_value = value;
     while current_value > 0 {
         result.push(current_value % 10);
         current_value = current_value / 10;
     }
 
-    result.into_iter().rev().collect()
-}
\ No newline at end of file
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digit_order() {
+        assert_eq!(i64_into_digits(&1002), vec![1, 0, 0, 2]);
+        assert_eq!(i64_into_digits_le(1002), vec![2, 0, 0, 1]);
+        assert_eq!(i64_into_digits_le(0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_negative_digits() {
+        assert_eq!(i64_into_digits(&-0), Vec::<usize>::new());
+        assert_eq!(i64_into_digits(&-7), vec![7]);
+        assert_eq!(i64_into_digits_le(-1002), vec![2, 0, 0, 1]);
+        assert_eq!(i64_into_digits(&i64::MIN), vec![9, 2, 2, 3, 3, 7, 2, 0, 3, 6, 8, 5, 4, 7, 7, 5, 8, 0, 8]);
+    }
+
+    #[test]
+    fn test_digits_into_i64() {
+        assert_eq!(digits_into_i64(&[1, 0, 0, 2]), 1002);
+        assert_eq!(digits_into_i64(&[0, 0, 7]), 7);
+        assert_eq!(digits_into_i64(&[]), 0);
+    }
+
+    #[test]
+    fn test_digits_round_trip() {
+        let large = [123_456_789, 19_690_720, 1 << 40, i64::MAX];
+        for value in (0..10_000).chain(large.iter().copied()) {
+            assert_eq!(digits_into_i64(&i64_into_digits(&value)), value);
+        }
+    }
+
+    #[test]
+    fn test_parameter_modes() {
+        assert_eq!(parameter_modes(1002), [0, 1, 0]);
+        assert_eq!(parameter_modes(21101), [1, 1, 2]);
+        assert_eq!(parameter_modes(204), [2, 0, 0]);
+        assert_eq!(parameter_modes(99), [0, 0, 0]);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::utils::grid::Point;
+
+pub fn manhattan(a: Point, b: Point) -> i64 {
+    a.manhattan_distance(b)
+}
+
+pub fn manhattan_from_origin(p: Point) -> i64 {
+    manhattan(p, Point::new(0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manhattan() {
+        assert_eq!(manhattan(Point::new(1, 2), Point::new(4, -2)), 7);
+        assert_eq!(manhattan(Point::new(-3, -3), Point::new(-3, -3)), 0);
+        assert_eq!(manhattan_from_origin(Point::new(3, 3)), 6);
+        assert_eq!(manhattan_from_origin(Point::new(-5, 2)), 7);
+    }
+}
@@ -0,0 +1,314 @@
+use anyhow::{anyhow, bail, Result};
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan_distance(&self, other: Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+impl FromStr for Point {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut parts = input.splitn(2, ',');
+        let mut component = || -> Result<i64> {
+            let part = parts.next().ok_or_else(|| anyhow!("Missing coordinate in point {:?}", input))?;
+            i64::from_str(part.trim()).map_err(|e| anyhow!("Invalid coordinate {:?} in point {:?}: {}", part, input, e))
+        };
+
+        Ok(Point::new(component()?, component()?))
+    }
+}
+
+// Flat storage is row-major: index = y * width + x
+pub fn to_index(p: Point, width: usize) -> usize {
+    p.y as usize * width + p.x as usize
+}
+
+pub fn from_index(i: usize, width: usize) -> Point {
+    Point::new((i % width) as i64, (i / width) as i64)
+}
+
+pub fn render_path(grid: &[Vec<char>], path: &[Point], marker: char) -> String {
+    let mut canvas = grid.to_vec();
+
+    for point in path {
+        if point.x < 0 || point.y < 0 {
+            continue;
+        }
+        if let Some(cell) = canvas.get_mut(point.y as usize).and_then(|row| row.get_mut(point.x as usize)) {
+            *cell = marker;
+        }
+    }
+
+    canvas.iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// A dense grid parsed from text, stored row-major like to_index
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl <T> Grid<T> {
+    // Blank lines are skipped, but every other line must be the same length
+    pub fn from_str<F: Fn(char) -> T>(input: &str, f: F) -> Result<Self> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        for line in input.lines().map(str::trim_end).filter(|line| !line.is_empty()) {
+            let row_width = line.chars().count();
+            match width {
+                Some(width) if width != row_width => bail!("Row {} has {} cells, expected {}", height, row_width, width),
+                _ => width = Some(row_width),
+            }
+
+            cells.extend(line.chars().map(&f));
+            height += 1;
+        }
+
+        Ok(Self { cells, width: width.unwrap_or(0), height })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            self.cells.get(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn neighbors4(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.neighbors(x, y, &[(0, -1), (1, 0), (0, 1), (-1, 0)])
+    }
+
+    pub fn neighbors8(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.neighbors(x, y, &[(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)])
+    }
+
+    fn neighbors(&self, x: usize, y: usize, offsets: &[(i64, i64)]) -> Vec<(usize, usize)> {
+        offsets.iter()
+            .map(|(dx, dy)| (x as i64 + dx, y as i64 + dy))
+            .filter(|&(x, y)| x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height)
+            .map(|(x, y)| (x as usize, y as usize))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows: Vec<String> = self.cells.chunks(self.width.max(1))
+            .map(|row| row.iter().collect())
+            .collect();
+
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+#[derive(Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(i64, i64), T>,
+}
+
+impl <T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self { cells: HashMap::new() }
+    }
+}
+
+impl <T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn set(&mut self, position: (i64, i64), value: T) {
+        self.cells.insert(position, value);
+    }
+
+    pub fn get(&self, position: (i64, i64)) -> Option<&T> {
+        self.cells.get(&position)
+    }
+
+    pub fn contains(&self, position: (i64, i64)) -> bool {
+        self.cells.contains_key(&position)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn bounds(&self) -> Option<((i64, i64), (i64, i64))> {
+        let xs = self.cells.keys().map(|&(x, _)| x);
+        let ys = self.cells.keys().map(|&(_, y)| y);
+
+        Some(((xs.clone().min()?, ys.clone().min()?), (xs.max()?, ys.max()?)))
+    }
+
+    pub fn count_where<F: Fn(&T) -> bool>(&self, predicate: F) -> usize {
+        self.cells.values().filter(|cell| predicate(cell)).count()
+    }
+
+    // Screen orientation, with y increasing downward. Cells that were never set are blank.
+    pub fn render<F: Fn(&T) -> char>(&self, cell_char: F) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+
+        (min_y..=max_y)
+            .map(|y| (min_x..=max_x)
+                .map(|x| self.get((x, y)).map_or(' ', &cell_char))
+                .collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl SparseGrid<u8> {
+    // Panels use the robot's frame where y increases upward, so rows are drawn
+    // from the highest y down to keep the painted letters the right way up.
+    pub fn render_panels(&self) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+
+        (min_y..=max_y).rev()
+            .map(|y| (min_x..=max_x)
+                .map(|x| match self.get((x, y)) {
+                    Some(1) => '█',
+                    _ => ' ',
+                })
+                .collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_point() {
+        assert_eq!(Point::from_str("3,4").unwrap(), Point::new(3, 4));
+        assert_eq!(Point::from_str(" -3 , -14\n").unwrap(), Point::new(-3, -14));
+
+        assert!(Point::from_str("").is_err());
+        assert!(Point::from_str("3").is_err());
+        assert!(Point::from_str("3,").is_err());
+        assert!(Point::from_str("3,4,5").is_err());
+        assert!(Point::from_str("x,4").is_err());
+        assert!(Point::from_str("- 3,4").is_err());
+    }
+
+    #[test]
+    fn test_index_conversion() {
+        assert_eq!(to_index(Point::new(0, 0), 25), 0);
+        assert_eq!(to_index(Point::new(3, 0), 25), 3);
+        assert_eq!(to_index(Point::new(3, 2), 25), 53);
+        assert_eq!(from_index(53, 25), Point::new(3, 2));
+
+        for i in 0..150 {
+            assert_eq!(to_index(from_index(i, 25), 25), i);
+        }
+    }
+
+    #[test]
+    fn test_render_path() {
+        let grid: Vec<Vec<char>> = ["#####", "#...#", "#.#.#", "#####"].iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        let path = [Point::new(1, 2), Point::new(1, 1), Point::new(2, 1), Point::new(3, 1), Point::new(3, 2)];
+        assert_eq!(render_path(&grid, &path, '*'), "#####\n#***#\n#*#*#\n#####");
+
+        let out_of_bounds = [Point::new(-1, 0), Point::new(5, 1), Point::new(0, 4), Point::new(2, 2)];
+        assert_eq!(render_path(&grid, &out_of_bounds, '*'), "#####\n#...#\n#.*.#\n#####");
+    }
+
+    #[test]
+    fn test_grid() {
+        let field = ".#..#\n.....\n#####\n....#\n...##\n";
+        let asteroids = Grid::from_str(field, |c| c == '#').unwrap();
+
+        assert_eq!((asteroids.width(), asteroids.height()), (5, 5));
+        assert_eq!(asteroids.get(1, 0), Some(&true));
+        assert_eq!(asteroids.get(2, 0), Some(&false));
+        assert_eq!(asteroids.get(4, 3), Some(&true));
+        assert_eq!(asteroids.get(5, 0), None);
+        assert_eq!(asteroids.get(0, 5), None);
+
+        assert_eq!(asteroids.neighbors4(0, 0), vec![(1, 0), (0, 1)]);
+        assert_eq!(asteroids.neighbors4(2, 2), vec![(2, 1), (3, 2), (2, 3), (1, 2)]);
+        assert_eq!(asteroids.neighbors8(4, 4), vec![(3, 3), (4, 3), (3, 4)]);
+        assert_eq!(asteroids.neighbors8(1, 1).len(), 8);
+
+        let chars = Grid::from_str(field, |c| c).unwrap();
+        assert_eq!(chars.to_string(), field.trim_end());
+
+        assert!(Grid::from_str(".#\n#", |c| c).is_err());
+    }
+
+    #[test]
+    fn test_render_sparse() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.render(|_: &char| '?'), "");
+
+        grid.set((-1, -1), 'a');
+        grid.set((1, -1), 'b');
+        grid.set((0, 0), 'c');
+        grid.set((1, 1), 'b');
+
+        assert_eq!(grid.render(|&cell| cell), "a b\n c \n  b");
+        assert_eq!(grid.render(|&cell| if cell == 'b' { '#' } else { '.' }), ". #\n . \n  #");
+        assert_eq!(grid.count_where(|&cell| cell == 'b'), 2);
+        assert_eq!(grid.count_where(|_| true), grid.len());
+    }
+
+    #[test]
+    fn test_render_panels() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.render_panels(), "");
+
+        // An "L" painted in the robot's y-up frame, with one panel painted back to black
+        for y in 0..3 {
+            grid.set((0, y), 1);
+        }
+        grid.set((1, 0), 1);
+        grid.set((2, 0), 1);
+        grid.set((1, 2), 0);
+
+        assert_eq!(grid.render_panels(), "█  \n█  \n███");
+    }
+}
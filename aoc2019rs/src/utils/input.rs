@@ -1,23 +1,50 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use rustyline::Editor;
 
-use std::io::{BufRead, BufReader};
+use std::collections::BTreeMap;
+use std::io::{BufReader, Read};
 use std::fs::File;
+use std::path::Path;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+static PROGRAM_CACHE: Mutex<BTreeMap<u8, Arc<Vec<i64>>>> = Mutex::new(BTreeMap::new());
 
 pub fn input_file_name(day_number: u8) -> String {
     format!("input/input{}", day_number)
 }
 
+pub fn fixture_file_name(name: &str) -> String {
+    format!("tests/{}", name)
+}
+
 pub fn input_file_reader(day_number: u8) -> Result<BufReader<File>> {
     let file = File::open(input_file_name(day_number))?;
     Ok(BufReader::new(file))
 }
 
+pub fn read_fixture(name: &str) -> Result<String> {
+    Ok(std::fs::read_to_string(fixture_file_name(name))?)
+}
+
 pub fn read_input_lines(day_number: u8) -> Result<Vec<String>> {
-    let file = input_file_reader(day_number)?;
-    let result = file.lines().flatten().collect();
-    Ok(result)
+    let contents = std::fs::read_to_string(input_file_name(day_number))?;
+    Ok(split_lines(&contents))
+}
+
+// Blank lines in the middle are kept, only the ones at the end are dropped
+fn split_lines(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+    while lines.last().map(|line| line.trim().is_empty()).unwrap_or(false) {
+        lines.pop();
+    }
+    lines
+}
+
+// For single-line puzzles like the day 8 image data
+pub fn read_input_chars(day_number: u8) -> Result<Vec<char>> {
+    let contents = std::fs::read_to_string(input_file_name(day_number))?;
+    Ok(contents.trim_end_matches(&['\r', '\n'][..]).chars().collect())
 }
 
 pub fn read_input_lines_as<T>(day_number: u8) -> Result<Vec<T>> 
@@ -31,25 +58,79 @@ where T: FromStr
 }
 
 pub fn read_input_list(day_number: u8, delimiter: u8) -> Result<Vec<String>> {
-    let file = input_file_reader(day_number)?;
-    let result = file
-        .split(delimiter)
-        .flatten()
-        .flat_map(String::from_utf8)
-        .collect();
-    Ok(result)
+    let contents = std::fs::read_to_string(input_file_name(day_number))?;
+    Ok(split_list(&contents, delimiter))
 }
 
-pub fn read_input_list_as<T>(day_number: u8, delimiter: u8) -> Result<Vec<T>> 
-where T: FromStr
+pub fn read_list_from<R: Read>(mut reader: R, delimiter: u8) -> Result<Vec<i64>> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+    parse_list(&contents, delimiter)
+}
+
+pub fn read_list_from_path<P: AsRef<Path>>(path: P, delimiter: u8) -> Result<Vec<i64>> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| anyhow!("Couldn't open {}: {}", path.display(), e))?;
+    read_list_from(BufReader::new(file), delimiter)
+}
+
+// Only the trailing newline and one trailing delimiter are dropped, so an empty
+// value anywhere else is kept and fails to parse
+fn split_list(contents: &str, delimiter: u8) -> Vec<String> {
+    let contents = contents.trim_end();
+    let contents = contents.strip_suffix(delimiter as char).unwrap_or(contents);
+
+    if contents.is_empty() {
+        return Vec::new();
+    }
+
+    contents.split(delimiter as char)
+        .map(String::from)
+        .collect()
+}
+
+pub fn read_input_list_as<T>(day_number: u8, delimiter: u8) -> Result<Vec<T>>
+where T: FromStr,
+      T::Err: std::error::Error + Send + Sync + 'static,
 {
-    let result = read_input_list(day_number, delimiter)?
-        .into_iter()
-        .flat_map(|element| T::from_str(&element))
-        .collect();
-    Ok(result)
+    let contents = std::fs::read_to_string(input_file_name(day_number))?;
+    parse_list(&contents, delimiter)
 }
 
+// A value that doesn't parse as T is an error rather than skipped
+fn parse_list<T>(contents: &str, delimiter: u8) -> Result<Vec<T>>
+where T: FromStr,
+      T::Err: std::error::Error + Send + Sync + 'static,
+{
+    split_list(contents, delimiter).into_iter()
+        .map(|element| T::from_str(&element).map_err(|e| anyhow::Error::new(e).context(format!("Invalid value {:?}", element))))
+        .collect()
+}
+
+// Comma separated like every Intcode program, and only read from disk the first time
+pub fn read_input_cached(day_number: u8) -> Result<Arc<Vec<i64>>> {
+    let mut cache = PROGRAM_CACHE.lock().map_err(|_| anyhow!("Input cache lock was poisoned"))?;
+
+    if let Some(program) = cache.get(&day_number) {
+        return Ok(Arc::clone(program));
+    }
+
+    let program = Arc::new(read_list_from_path(input_file_name(day_number), b',')?);
+    cache.insert(day_number, Arc::clone(&program));
+    Ok(program)
+}
+
+pub fn read_input_list_radix(day_number: u8, delimiter: u8, radix: u32) -> Result<Vec<i64>> {
+    let contents = std::fs::read_to_string(input_file_name(day_number))?;
+    parse_list_radix(&contents, delimiter, radix)
+}
+
+fn parse_list_radix(contents: &str, delimiter: u8, radix: u32) -> Result<Vec<i64>> {
+    split_list(contents, delimiter).into_iter()
+        .map(|element| i64::from_str_radix(&element, radix)
+            .map_err(|e| anyhow!("Invalid base {} value {:?}: {}", radix, element, e)))
+        .collect()
+}
 
 pub fn read_input() -> Result<String> {
     read_input_with_prompt("Enter input: ")
@@ -60,3 +141,84 @@ pub fn read_input_with_prompt(prompt: &str) -> Result<String> {
     let readline = rl.readline(prompt);
     Ok(readline?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_lines() {
+        let lines = split_lines(&read_fixture("orbit_lines").unwrap());
+        assert_eq!(lines, vec!["COM)B", "B)C", "", "C)D"]);
+
+        assert_eq!(split_lines("12\n14"), vec!["12", "14"]);
+        assert_eq!(split_lines("\n\n"), Vec::<String>::new());
+        assert_eq!(split_lines(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_read_input_chars() {
+        let chars = read_input_chars(8).unwrap();
+        assert!(!chars.is_empty());
+        assert!(chars.iter().all(char::is_ascii_digit));
+        assert!(read_input_chars(99).is_err());
+    }
+
+    #[test]
+    fn test_split_list_trailing_newline() {
+        assert_eq!(split_list("1,2,99\n", b','), vec!["1", "2", "99"]);
+        assert_eq!(split_list("1,2,99\r\n\n", b','), vec!["1", "2", "99"]);
+        assert_eq!(split_list("1,2,99,\n", b','), vec!["1", "2", "99"]);
+        assert_eq!(split_list("", b','), Vec::<String>::new());
+        assert_eq!(split_list("1,,2", b','), vec!["1", "", "2"]);
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let contents = read_fixture("digits_list").unwrap();
+
+        let small: Vec<u8> = parse_list(&contents, b',').unwrap();
+        let large: Vec<i64> = parse_list(&contents, b',').unwrap();
+        assert_eq!(small, vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5]);
+        assert_eq!(large, small.iter().map(|&digit| digit as i64).collect::<Vec<i64>>());
+
+        let error = parse_list::<u8>("1,300,2", b',').unwrap_err();
+        assert_eq!(error.to_string(), "Invalid value \"300\"");
+        assert!(parse_list::<usize>("4,-1", b',').is_err());
+        assert_eq!(parse_list::<i64>("4,-1", b',').unwrap(), vec![4, -1]);
+    }
+
+    #[test]
+    fn test_read_list_from() {
+        assert_eq!(read_list_from(std::io::Cursor::new(b"1,2,3"), b',').unwrap(), vec![1, 2, 3]);
+        assert_eq!(read_list_from(&b"4 -5\n"[..], b' ').unwrap(), vec![4, -5]);
+        assert!(read_list_from(&b"1,x,3"[..], b',').is_err());
+        assert!(read_list_from(&b"1,,2"[..], b',').is_err());
+        assert!(parse_list::<i64>("1,,2", b',').is_err());
+        assert!(parse_list_radix("1,,2", b',', 10).is_err());
+    }
+
+    #[test]
+    fn test_read_list_from_path() {
+        assert_eq!(read_list_from_path(input_file_name(2), b',').unwrap(), read_input_list_as::<i64>(2, b',').unwrap());
+        assert!(read_list_from_path("input/missing", b',').is_err());
+    }
+
+    #[test]
+    fn test_read_input_cached() {
+        let first = read_input_cached(2).unwrap();
+        let second = read_input_cached(2).unwrap();
+
+        assert_eq!(first, second);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(*first, read_input_list_as::<i64>(2, b',').unwrap());
+        assert!(read_input_cached(99).is_err());
+    }
+
+    #[test]
+    fn test_parse_list_radix() {
+        assert_eq!(parse_list_radix("ff,-1A,0\n", b',', 16).unwrap(), vec![255, -26, 0]);
+        assert_eq!(parse_list_radix("101 11", b' ', 2).unwrap(), vec![5, 3]);
+        assert!(parse_list_radix("12,1g", b',', 16).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::utils::search::cheapest_path;
+
+// The shortest path by step count, including both the start and the goal
+pub fn bfs<N, G, F>(start: N, is_goal: G, neighbors: F) -> Option<Vec<N>>
+where N: Hash + Eq + Clone,
+      G: Fn(&N) -> bool,
+      F: Fn(&N) -> Vec<N>,
+{
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    parents.insert(start.clone(), None);
+    queue.push_back(start);
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some(unwind_path(&parents, node));
+        }
+
+        for next in neighbors(&node) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+// Like search::astar with a zero heuristic, but also returns the path, including both ends
+pub fn dijkstra<N, G, F>(start: N, is_goal: G, neighbors: F) -> Option<(usize, Vec<N>)>
+where N: Hash + Eq + Clone,
+      G: Fn(&N) -> bool,
+      F: Fn(&N) -> Vec<(N, usize)>,
+{
+    cheapest_path(start, neighbors, |_| 0, is_goal)
+        .map(|(cost, goal, parents)| (cost, unwind_path(&parents, goal)))
+}
+
+fn unwind_path<N: Hash + Eq + Clone>(parents: &HashMap<N, Option<N>>, goal: N) -> Vec<N> {
+    let mut path = vec![goal];
+    while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::search::astar;
+
+    fn weighted_graph(node: &char) -> Vec<(char, usize)> {
+        match node {
+            'A' => vec![('B', 7), ('C', 9), ('F', 14)],
+            'B' => vec![('A', 7), ('C', 10), ('D', 15)],
+            'C' => vec![('A', 9), ('B', 10), ('D', 11), ('F', 2)],
+            'D' => vec![('B', 15), ('C', 11), ('E', 6)],
+            'E' => vec![('D', 6), ('F', 9)],
+            'F' => vec![('A', 14), ('C', 2), ('E', 9)],
+            _ => vec![],
+        }
+    }
+
+    fn unweighted_graph(node: &char) -> Vec<char> {
+        weighted_graph(node).into_iter().map(|(next, _)| next).collect()
+    }
+
+    fn open_grid(node: &(i64, i64)) -> Vec<((i64, i64), usize)> {
+        let (x, y) = *node;
+        vec![(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter()
+            .filter(|&(x, y)| x >= 0 && y >= 0 && x < 10 && y < 10)
+            .filter(|&(x, y)| !(x == 5 && y < 8))
+            .map(|cell| (cell, 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        assert_eq!(bfs('A', |&node| node == 'E', unweighted_graph), Some(vec!['A', 'F', 'E']));
+        assert_eq!(bfs('A', |&node| node == 'A', unweighted_graph), Some(vec!['A']));
+        assert_eq!(bfs('A', |&node| node == 'Z', unweighted_graph), None);
+
+        let path = bfs((0, 0), |&node| node == (9, 0), |&node| open_grid(&node).into_iter().map(|(cell, _)| cell).collect()).unwrap();
+        assert_eq!(path.len(), 26);
+        assert_eq!((path[0], path[25]), ((0, 0), (9, 0)));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        assert_eq!(dijkstra('A', |&node| node == 'E', weighted_graph), Some((20, vec!['A', 'C', 'F', 'E'])));
+        assert_eq!(dijkstra('A', |&node| node == 'A', weighted_graph), Some((0, vec!['A'])));
+        assert_eq!(dijkstra('A', |&node| node == 'Z', weighted_graph), None);
+
+        let (cost, path) = dijkstra((0, 0), |&node| node == (9, 0), open_grid).unwrap();
+        assert_eq!(Some(cost), astar((0, 0), open_grid, |_| 0, |&node| node == (9, 0)));
+        assert_eq!(path.len(), cost + 1);
+    }
+}
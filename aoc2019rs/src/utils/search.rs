@@ -0,0 +1,128 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+struct Candidate<N> {
+    estimate: usize,
+    cost: usize,
+    node: N,
+}
+
+impl <N> PartialEq for Candidate<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl <N> Eq for Candidate<N> {}
+
+impl <N> PartialOrd for Candidate<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl <N> Ord for Candidate<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.estimate.cmp(&other.estimate)
+    }
+}
+
+// Returns the cost of the cheapest path to a goal. The result is only optimal if
+// the heuristic never overestimates, and a zero heuristic makes this Dijkstra.
+pub fn astar<N, F, H, G>(start: N, neighbors: F, heuristic: H, is_goal: G) -> Option<usize>
+where N: Hash + Eq + Clone,
+      F: Fn(&N) -> Vec<(N, usize)>,
+      H: Fn(&N) -> usize,
+      G: Fn(&N) -> bool,
+{
+    cheapest_path(start, neighbors, heuristic, is_goal).map(|(cost, _, _)| cost)
+}
+
+// The goal reached and what it cost, with the parent of every node seen on the way
+pub(crate) fn cheapest_path<N, F, H, G>(start: N, neighbors: F, heuristic: H, is_goal: G) -> Option<(usize, N, HashMap<N, Option<N>>)>
+where N: Hash + Eq + Clone,
+      F: Fn(&N) -> Vec<(N, usize)>,
+      H: Fn(&N) -> usize,
+      G: Fn(&N) -> bool,
+{
+    let mut best_costs = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    best_costs.insert(start.clone(), 0);
+    parents.insert(start.clone(), None);
+    queue.push(Reverse(Candidate { estimate: heuristic(&start), cost: 0, node: start }));
+
+    while let Some(Reverse(Candidate { cost, node, .. })) = queue.pop() {
+        if is_goal(&node) {
+            return Some((cost, node, parents));
+        }
+
+        if let Some(&best) = best_costs.get(&node) {
+            if cost > best {
+                continue;
+            }
+        }
+
+        for (next, step_cost) in neighbors(&node) {
+            let next_cost = cost + step_cost;
+            if let Some(&best) = best_costs.get(&next) {
+                if next_cost >= best {
+                    continue;
+                }
+            }
+
+            best_costs.insert(next.clone(), next_cost);
+            parents.insert(next.clone(), Some(node.clone()));
+            queue.push(Reverse(Candidate { estimate: next_cost + heuristic(&next), cost: next_cost, node: next }));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted_graph(node: &char) -> Vec<(char, usize)> {
+        match node {
+            'A' => vec![('B', 7), ('C', 9), ('F', 14)],
+            'B' => vec![('A', 7), ('C', 10), ('D', 15)],
+            'C' => vec![('A', 9), ('B', 10), ('D', 11), ('F', 2)],
+            'D' => vec![('B', 15), ('C', 11), ('E', 6)],
+            'E' => vec![('D', 6), ('F', 9)],
+            'F' => vec![('A', 14), ('C', 2), ('E', 9)],
+            _ => vec![],
+        }
+    }
+
+    fn open_grid(node: &(i64, i64)) -> Vec<((i64, i64), usize)> {
+        let (x, y) = *node;
+        vec![(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter()
+            .filter(|&(x, y)| x >= 0 && y >= 0 && x < 10 && y < 10)
+            .filter(|&(x, y)| !(x == 5 && y < 8))
+            .map(|cell| (cell, 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_weighted() {
+        assert_eq!(astar('A', weighted_graph, |_| 0, |&node| node == 'E'), Some(20));
+        assert_eq!(astar('A', weighted_graph, |_| 0, |&node| node == 'A'), Some(0));
+        assert_eq!(astar('A', weighted_graph, |_| 0, |&node| node == 'Z'), None);
+    }
+
+    #[test]
+    fn test_heuristic_matches_zero_heuristic() {
+        let target = (9, 0);
+        let manhattan = |&(x, y): &(i64, i64)| ((target.0 - x).abs() + (target.1 - y).abs()) as usize;
+
+        let dijkstra = astar((0, 0), open_grid, |_| 0, |&node| node == target);
+        let guided = astar((0, 0), open_grid, manhattan, |&node| node == target);
+
+        assert_eq!(dijkstra, Some(25));
+        assert_eq!(guided, dijkstra);
+    }
+}